@@ -1,24 +1,216 @@
-use darling::FromMeta;
+use darling::{FromField, FromMeta};
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{self, parse_macro_input, AttributeArgs, Ident, ItemStruct};
+use syn::{self, parse_macro_input, AttributeArgs, Fields, Ident, ItemStruct, LitStr};
 
+/// Emit `error` alongside the unchanged item, so the only diagnostic is the macro's own
+fn with_error(ast: &ItemStruct, error: proc_macro2::TokenStream) -> TokenStream {
+    quote! {
+        #error
+        #ast
+    }
+    .into()
+}
+
+fn check_no_args(macro_name: &str, args: &AttributeArgs) -> syn::Result<()> {
+    match args.first() {
+        Some(arg) => Err(syn::Error::new_spanned(
+            arg,
+            format!("{macro_name} does not take any arguments"),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The serde renames only make sense for structs with named fields
+fn check_named_fields(macro_name: &str, ast: &ItemStruct) -> syn::Result<()> {
+    match &ast.fields {
+        Fields::Named(_) => Ok(()),
+        Fields::Unnamed(fields) => Err(syn::Error::new_spanned(
+            fields,
+            format!("{macro_name} only supports structs with named fields, not tuple structs"),
+        )),
+        Fields::Unit => Err(syn::Error::new_spanned(
+            &ast.ident,
+            format!("{macro_name} only supports structs with named fields, not unit structs"),
+        )),
+    }
+}
+
+/// Acronyms the API writes in capitals when they end a field name, e.g. `AthleteID` and
+/// `GenuinePB`. Elsewhere they are capitalised like any other word, e.g. `WasPbRun`
+const ACRONYMS: [&str; 3] = ["id", "pb", "url"];
+
+/// The API's name for a field, `home_run_id` becomes `HomeRunID`
+fn api_field_name(ident: &Ident) -> String {
+    let name = ident.to_string();
+    let name = name.trim_start_matches("r#");
+    let words = name.split('_').filter(|word| !word.is_empty());
+    let last = words.clone().count().saturating_sub(1);
+    words
+        .enumerate()
+        .map(|(index, word)| {
+            if index == last && ACRONYMS.contains(&word) {
+                word.to_ascii_uppercase()
+            } else {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                    .unwrap_or_default()
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Default, FromMeta)]
+#[darling(default)]
+struct ParkrunModelArgs {
+    /// Default every field that is missing from the response instead of failing to decode
+    lenient: bool,
+}
+
+#[derive(Debug, FromField)]
+#[darling(attributes(parkrun))]
+struct ParkrunFieldArgs {
+    ident: Option<Ident>,
+    /// The API's name for the field, when it doesn't follow the usual conventions
+    #[darling(default)]
+    field: Option<LitStr>,
+    /// Use the field type's default when the field is missing
+    #[darling(default)]
+    default: bool,
+}
+
+/// Which serde options are already set on a field, so they aren't set twice
+#[derive(Default)]
+struct SerdeOptions {
+    rename: bool,
+    default: bool,
+}
+
+impl SerdeOptions {
+    fn from_field(field: &syn::Field) -> Self {
+        let mut options = SerdeOptions::default();
+        let metas = field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("serde"))
+            .filter_map(|attr| attr.parse_meta().ok());
+        for meta in metas {
+            let syn::Meta::List(list) = meta else {
+                continue;
+            };
+            for nested in list.nested {
+                let syn::NestedMeta::Meta(meta) = nested else {
+                    continue;
+                };
+                if meta.path().is_ident("rename") {
+                    options.rename = true;
+                } else if meta.path().is_ident("default") {
+                    options.default = true;
+                }
+            }
+        }
+        options
+    }
+}
+
+/// Remove field attributes for this macro and serde, which nothing can use when the macro fails
+fn strip_field_attrs(mut ast: ItemStruct) -> ItemStruct {
+    for field in ast.fields.iter_mut() {
+        field
+            .attrs
+            .retain(|attr| !attr.path.is_ident("parkrun") && !attr.path.is_ident("serde"));
+    }
+    ast
+}
+
+/// Add a serde rename for each field following the API's naming, and defaults where requested
+fn add_field_attrs(ast: &mut ItemStruct, args: &ParkrunModelArgs) -> darling::Result<()> {
+    let mut errors = Vec::new();
+    for field in ast.fields.iter_mut() {
+        let field_args = match ParkrunFieldArgs::from_field(field) {
+            Ok(field_args) => field_args,
+            Err(error) => {
+                errors.push(error);
+                continue;
+            }
+        };
+        let serde_options = SerdeOptions::from_field(field);
+        field.attrs.retain(|attr| !attr.path.is_ident("parkrun"));
+
+        match (&field_args.field, serde_options.rename) {
+            (Some(name), true) => {
+                errors.push(
+                    darling::Error::custom(
+                        "use either #[parkrun(field = ...)] or #[serde(rename = ...)], not both",
+                    )
+                    .with_span(name),
+                );
+                continue;
+            }
+            (Some(name), false) => field
+                .attrs
+                .push(syn::parse_quote!(#[serde(rename = #name)])),
+            (None, true) => {}
+            (None, false) => {
+                let name = api_field_name(field_args.ident.as_ref().unwrap());
+                field
+                    .attrs
+                    .push(syn::parse_quote!(#[serde(rename = #name)]));
+            }
+        }
+
+        if (args.lenient || field_args.default) && !serde_options.default {
+            field.attrs.push(syn::parse_quote!(#[serde(default)]));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(darling::Error::multiple(errors))
+    }
+}
+
+/// Derive serde and the usual traits for a parkrun API model. Fields are named the way the API
+/// names them, `PascalCase` with a trailing `ID`, `PB` or `URL` in capitals. Use
+/// `#[parkrun(field = "...")]` for fields that don't follow the pattern, `#[parkrun(default)]` for
+/// fields the API sometimes leaves out, or `#[parkrun_model(lenient)]` to default every missing
+/// field
 #[proc_macro_attribute]
-pub fn parkrun_model(_: TokenStream, item: TokenStream) -> TokenStream {
-    let ast = parse_macro_input!(item as ItemStruct);
+pub fn parkrun_model(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut ast = parse_macro_input!(item as ItemStruct);
+    let args_ast = parse_macro_input!(attr as AttributeArgs);
+    if let Err(error) = check_named_fields("parkrun_model", &ast) {
+        return with_error(&strip_field_attrs(ast), error.to_compile_error());
+    }
+    let args = match ParkrunModelArgs::from_list(&args_ast) {
+        Ok(args) => args,
+        Err(error) => return with_error(&strip_field_attrs(ast), error.write_errors()),
+    };
+    if let Err(error) = add_field_attrs(&mut ast, &args) {
+        return with_error(&strip_field_attrs(ast), error.write_errors());
+    }
 
     quote! {
         #[allow(clippy::derive_partial_eq_without_eq)]
         #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-        #[serde(rename_all = "PascalCase")]
         #ast
     }
     .into()
 }
 
 #[proc_macro_attribute]
-pub fn parkrun_request_args(_: TokenStream, item: TokenStream) -> TokenStream {
+pub fn parkrun_request_args(attr: TokenStream, item: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(item as ItemStruct);
+    let args = parse_macro_input!(attr as AttributeArgs);
+    if let Err(error) = check_no_args("parkrun_request_args", &args)
+        .and_then(|_| check_named_fields("parkrun_request_args", &ast))
+    {
+        return with_error(&ast, error.to_compile_error());
+    }
 
     quote! {
         #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -30,19 +222,70 @@ pub fn parkrun_request_args(_: TokenStream, item: TokenStream) -> TokenStream {
 
 #[derive(Debug, FromMeta)]
 struct ParkrunListArgs {
-    endpoint: String,
+    endpoint: LitStr,
     args_type: Ident,
     data_key: Ident,
 }
 
+impl ParkrunListArgs {
+    fn parse(args: &AttributeArgs) -> Result<Self, proc_macro2::TokenStream> {
+        let args = ParkrunListArgs::from_list(args).map_err(|error| error.write_errors())?;
+        if !args.endpoint.value().starts_with('/') {
+            return Err(syn::Error::new_spanned(
+                &args.endpoint,
+                "endpoint must be a path starting with `/`, e.g. \"/v1/events\"",
+            )
+            .to_compile_error());
+        }
+        args.endpoint_format()
+            .map_err(|error| error.to_compile_error())?;
+        Ok(args)
+    }
+
+    /// Split an endpoint such as `/v1/events/{event_number}/runs` into a format string and the
+    /// fields of the args type that fill in its placeholders
+    fn endpoint_format(&self) -> syn::Result<(String, Vec<Ident>)> {
+        let endpoint = self.endpoint.value();
+        let mut format = String::new();
+        let mut fields = Vec::new();
+        let mut rest = endpoint.as_str();
+        while let Some(start) = rest.find('{') {
+            let end = rest[start..].find('}').ok_or_else(|| {
+                syn::Error::new_spanned(&self.endpoint, "unclosed `{` in endpoint")
+            })? + start;
+            let field = &rest[start + 1..end];
+            let field = syn::parse_str::<Ident>(field).map_err(|_| {
+                syn::Error::new_spanned(
+                    &self.endpoint,
+                    format!("endpoint placeholder `{{{field}}}` must be a valid identifier"),
+                )
+            })?;
+            format.push_str(&rest[..start]);
+            format.push_str("{}");
+            fields.push(Ident::new(&field.to_string(), self.endpoint.span()));
+            rest = &rest[end + 1..];
+        }
+        if rest.contains('}') {
+            return Err(syn::Error::new_spanned(
+                &self.endpoint,
+                "unmatched `}` in endpoint",
+            ));
+        }
+        format.push_str(rest);
+        Ok((format, fields))
+    }
+}
+
 #[proc_macro_attribute]
 pub fn parkrun_list(attr: TokenStream, item: TokenStream) -> TokenStream {
     // Construct a representation of Rust code as a syntax tree
     // that we can manipulate
     let ast = parse_macro_input!(item as ItemStruct);
     let args_ast = parse_macro_input!(attr as AttributeArgs);
-    // println!("Args: {:?}", args);
-    let args = ParkrunListArgs::from_list(&args_ast).unwrap();
+    let args = match ParkrunListArgs::parse(&args_ast) {
+        Ok(args) => args,
+        Err(error) => return with_error(&ast, error),
+    };
 
     // Build the trait implementation
     impl_parkrun_list(ast, args)
@@ -51,9 +294,22 @@ pub fn parkrun_list(attr: TokenStream, item: TokenStream) -> TokenStream {
 fn impl_parkrun_list(ast: ItemStruct, args: ParkrunListArgs) -> TokenStream {
     // Object name
     let name = &ast.ident;
-    let endpoint = args.endpoint;
     let args_type = &args.args_type;
     let data_key = &args.data_key;
+    // Endpoints with placeholders are filled in from the args, e.g. `/v1/events/{event_number}`.
+    // Each value is encoded as a single path segment
+    let endpoint = match args.endpoint_format() {
+        Ok((format, fields)) if !fields.is_empty() => quote! {
+            format!(
+                #format,
+                #(crate::client::encode_path_segment(&args.#fields.to_string())),*
+            ).as_str()
+        },
+        _ => {
+            let endpoint = &args.endpoint;
+            quote! { #endpoint }
+        }
+    };
 
     let list_response_ident = Ident::new(format!("List{}Response", name).as_str(), name.span());
     let list_def = quote! {
@@ -64,47 +320,52 @@ fn impl_parkrun_list(ast: ItemStruct, args: ParkrunListArgs) -> TokenStream {
 
         #[async_trait(?Send)]
         impl Listable<#args_type> for #name {
-            async fn list(args: #args_type, parkrun_client: &mut AuthenticatedParkrunClient) -> Result<Vec<#name>, Box<dyn std::error::Error + Send + Sync>> {
+            async fn list(args: #args_type, parkrun_client: &mut AuthenticatedParkrunClient) -> crate::Result<Vec<#name>> {
+                futures::TryStreamExt::try_collect(Self::stream(args, parkrun_client)).await
+            }
+
+            async fn list_page(
+                args: #args_type,
+                offset: u32,
+                limit: u32,
+                parkrun_client: &mut AuthenticatedParkrunClient,
+            ) -> crate::Result<ListPage<#name>> {
+                let request = parkrun_client
+                    .request(reqwest::Method::GET, #endpoint)?
+                    .query(&args)
+                    .query(&[("offset", offset), ("limit", limit)]);
+
+                crate::client::pagination::fetch_page(
+                    parkrun_client,
+                    request,
+                    |response: #list_response_ident| response.#data_key,
+                )
+                .await
+            }
 
-                // Make list call with params
+            fn stream<'a>(
+                args: #args_type,
+                parkrun_client: &'a mut AuthenticatedParkrunClient,
+            ) -> futures::stream::LocalBoxStream<'a, crate::Result<#name>>
+            where
+                Self: 'a,
+            {
+                // Make list call with params, then follow the next page links
                 let request = parkrun_client
                     .request(reqwest::Method::GET, #endpoint)
-                    .query(&args);
-
-                let mut response = parkrun_client.send_request_with_refresh(request)
-                    .await?
-                    .json::<ListResponse<#list_response_ident>>()
-                    .await?;
-
-                let mut items: Vec<#name> = Vec::new();
-                items.extend(response.data.#data_key);
-
-                // While there is a next page token
-                while let Some(next_page) = response.links.iter().find_map(|link| {
-                    if link.rel == "next" {
-                        Some(link.href.clone())
-                    } else {
-                        None
-                    }
-                }) {
-                    // Update the response with the next page response
-                    let request = parkrun_client.request(reqwest::Method::GET, &next_page.as_str()[2..]);
-                    response = parkrun_client.send_request_with_refresh(request)
-                        .await?
-                        .json::<ListResponse<#list_response_ident>>()
-                        .await?;
-
-                    // And push the items to the output list
-                    items.extend(response.data.#data_key);
-                }
+                    .map(|request| request.query(&args));
 
-                Ok(items)
+                crate::client::pagination::stream_pages(
+                    parkrun_client,
+                    request,
+                    |response: #list_response_ident| response.#data_key,
+                )
             }
         }
     };
-    return quote! {
+    quote! {
         #ast
         #list_def
     }
-    .into();
+    .into()
 }
@@ -0,0 +1,10 @@
+use parkrust_derive::parkrun_model;
+
+#[parkrun_model()]
+pub struct RunResult {
+    #[parkrun(field = "RunId")]
+    #[serde(rename = "RunID")]
+    pub run_id: String,
+}
+
+fn main() {}
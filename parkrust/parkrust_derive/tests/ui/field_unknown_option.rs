@@ -0,0 +1,9 @@
+use parkrust_derive::parkrun_model;
+
+#[parkrun_model()]
+pub struct Athlete {
+    #[parkrun(name = "AthleteId")]
+    pub athlete_id: String,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use parkrust_derive::parkrun_list;
+
+#[parkrun_list(
+    endpoint = "/v1/events/{event number}/runs",
+    args_type = "RunsQuery",
+    data_key = "runs"
+)]
+pub struct Run {
+    pub run_id: String,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use parkrust_derive::parkrun_list;
+
+#[parkrun_list(args_type = "EventsQuery", data_key = "events")]
+pub struct Event {
+    pub event_name: String,
+}
+
+fn main() {}
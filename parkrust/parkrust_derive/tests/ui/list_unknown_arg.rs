@@ -0,0 +1,13 @@
+use parkrust_derive::parkrun_list;
+
+#[parkrun_list(
+    endpoint = "/v1/events",
+    args_type = "EventsQuery",
+    data_key = "events",
+    page_size = 100
+)]
+pub struct Event {
+    pub event_name: String,
+}
+
+fn main() {}
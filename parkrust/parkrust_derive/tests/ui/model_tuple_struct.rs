@@ -0,0 +1,6 @@
+use parkrust_derive::parkrun_model;
+
+#[parkrun_model()]
+pub struct RunTime(String);
+
+fn main() {}
@@ -0,0 +1,8 @@
+use parkrust_derive::parkrun_model;
+
+#[parkrun_model(rename_all = "camelCase")]
+pub struct Athlete {
+    pub first_name: String,
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use parkrust_derive::parkrun_request_args;
+
+#[parkrun_request_args()]
+pub struct ResultsQuery(String);
+
+fn main() {}
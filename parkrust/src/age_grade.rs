@@ -0,0 +1,409 @@
+//! Age grading, following the World Masters Athletics (WMA) road running method parkrun uses.
+//!
+//! An age grade compares a time to the best time possible for the athlete's age and sex over the
+//! distance. The age standard is the open standard (the best time at any age) divided by the age
+//! factor, and the age grade is the age standard as a percentage of the time run.
+//!
+//! An [`AgeGradeTable`] holds open standards and age factors for each sex and distance, with a
+//! factor for every single year of age, read from CSV in the layout of the WMA road tables with
+//! [`AgeGradeTable::from_csv`].
+//!
+//! The free functions use [`AgeGradeTable::road`], built from `age_grade/male_road.csv` and
+//! `age_grade/female_road.csv` embedded in the crate. Those files currently hold an approximation
+//! of the WMA road tables, with the same factors for every distance, so grades are typically
+//! within a percentage point or so of parkrun's. Dropping the official WMA tables in, in the same
+//! layout, makes the free functions reproduce parkrun's grades without any code changes.
+
+use chrono::Duration;
+use std::ops::RangeInclusive;
+use std::sync::OnceLock;
+
+use crate::models::parkrun::{AgeCategory, AgeClass, RunTime, Sex, YOUNGEST_JUNIOR_AGE};
+
+/// The built in tables, in the layout [`AgeGradeTable::from_csv`] reads
+const MALE_ROAD_TABLE: &str = include_str!("age_grade/male_road.csv");
+const FEMALE_ROAD_TABLE: &str = include_str!("age_grade/female_road.csv");
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid age grade table, line {line}: {message}")]
+pub struct ParseAgeGradeTableError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// Open standard and age factors for one distance
+#[derive(Debug, Clone, PartialEq)]
+struct Column {
+    distance_metres: u32,
+    /// Seconds
+    open_standard: f64,
+    /// Indexed by age from the table's youngest age
+    factors: Vec<f64>,
+}
+
+/// Every distance for one sex, ordered by distance
+#[derive(Debug, Clone, PartialEq)]
+struct SexTable {
+    youngest_age: u8,
+    columns: Vec<Column>,
+}
+
+/// Open standards and age factors by sex, distance and single year of age
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgeGradeTable {
+    male: SexTable,
+    female: SexTable,
+}
+
+/// Linear interpolation of `y` at `x` between `(x0, y0)` and `(x1, y1)`
+fn interpolate(x: f64, (x0, y0): (f64, f64), (x1, y1): (f64, f64)) -> f64 {
+    if x1 == x0 {
+        return y0;
+    }
+    y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+}
+
+fn seconds(duration: Duration) -> f64 {
+    duration.num_milliseconds() as f64 / 1000.0
+}
+
+fn duration(seconds: f64) -> Duration {
+    Duration::milliseconds((seconds * 1000.0).round() as i64)
+}
+
+/// Parse a distance heading such as `5000`, `5 km`, `10K`, `Mile`, `H. Mar` or `Marathon`
+fn parse_distance(heading: &str) -> Option<u32> {
+    let key = heading
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect::<String>()
+        .to_ascii_lowercase();
+    match key.as_str() {
+        "mile" | "1mile" => return Some(1609),
+        "halfmarathon" | "halfmar" | "hmar" | "hm" => return Some(21097),
+        "marathon" | "mar" => return Some(42195),
+        _ => {}
+    }
+    if let Ok(metres) = key.parse::<u32>() {
+        return Some(metres);
+    }
+    let km = key
+        .strip_suffix("km")
+        .or_else(|| key.strip_suffix('k'))?
+        .parse::<f64>()
+        .ok()?;
+    Some((km * 1000.0).round() as u32)
+}
+
+/// Parse an open standard as seconds e.g. `779`, or as a time e.g. `12:59`
+fn parse_open_standard(value: &str) -> Option<f64> {
+    value
+        .parse::<f64>()
+        .ok()
+        .or_else(|| Some(f64::from(value.parse::<RunTime>().ok()?.num_seconds())))
+        .filter(|seconds| *seconds > 0.0)
+}
+
+impl SexTable {
+    /// Parse one sex's table. The first row is `Age` followed by the distance of each column, the
+    /// next is `OC` followed by the open standards, then a row for each single year of age
+    /// starting with the age followed by the factors. Blank lines and lines starting with `#` are
+    /// skipped
+    fn from_csv(csv: &str) -> Result<Self, ParseAgeGradeTableError> {
+        let mut rows = csv
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(line, row)| (line, row.split(',').map(str::trim).collect::<Vec<&str>>()));
+        let error = |line: usize, message: String| ParseAgeGradeTableError { line, message };
+
+        let (line, header) = rows
+            .next()
+            .ok_or_else(|| error(0, String::from("missing header")))?;
+        if !header[0].eq_ignore_ascii_case("age") {
+            return Err(error(line, String::from("header must start with `Age`")));
+        }
+        let distances = header[1..]
+            .iter()
+            .map(|heading| {
+                parse_distance(heading)
+                    .ok_or_else(|| error(line, format!("invalid distance {heading:?}")))
+            })
+            .collect::<Result<Vec<u32>, _>>()?;
+        if distances.is_empty() {
+            return Err(error(line, String::from("no distances")));
+        }
+        let width = header.len();
+
+        let (line, open_row) = rows
+            .next()
+            .ok_or_else(|| error(line, String::from("missing open standards")))?;
+        let open_label = open_row[0].to_ascii_lowercase();
+        if !(open_label.starts_with("oc") || open_label.starts_with("open")) {
+            return Err(error(line, String::from("expected open standards (`OC`)")));
+        }
+        if open_row.len() != width {
+            return Err(error(line, format!("expected {width} columns")));
+        }
+        let mut columns = distances
+            .iter()
+            .zip(&open_row[1..])
+            .map(|(distance_metres, value)| {
+                Ok(Column {
+                    distance_metres: *distance_metres,
+                    open_standard: parse_open_standard(value)
+                        .ok_or_else(|| error(line, format!("invalid open standard {value:?}")))?,
+                    factors: Vec::new(),
+                })
+            })
+            .collect::<Result<Vec<Column>, _>>()?;
+
+        let mut youngest_age = None;
+        let mut next_age = None;
+        for (line, row) in rows {
+            if row.len() != width {
+                return Err(error(line, format!("expected {width} columns")));
+            }
+            let age = row[0]
+                .parse::<u8>()
+                .map_err(|_| error(line, format!("invalid age {:?}", row[0])))?;
+            if next_age.is_some_and(|next_age| age != next_age) {
+                return Err(error(
+                    line,
+                    format!("ages must be consecutive, found {age}"),
+                ));
+            }
+            youngest_age.get_or_insert(age);
+            next_age = age.checked_add(1);
+            for (column, value) in columns.iter_mut().zip(&row[1..]) {
+                let factor = value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|factor| *factor > 0.0 && *factor <= 1.5)
+                    .ok_or_else(|| error(line, format!("invalid factor {value:?}")))?;
+                column.factors.push(factor);
+            }
+        }
+        let youngest_age = youngest_age.ok_or_else(|| error(line, String::from("no ages")))?;
+
+        columns.sort_by_key(|column| column.distance_metres);
+        Ok(SexTable {
+            youngest_age,
+            columns,
+        })
+    }
+
+    /// The columns either side of `distance_metres`, the nearest two when it's outside the table
+    fn columns_around(&self, distance_metres: u32) -> (&Column, &Column) {
+        let columns = &self.columns;
+        let last = columns.len() - 1;
+        let upper = columns
+            .iter()
+            .position(|column| distance_metres <= column.distance_metres)
+            .unwrap_or(last)
+            .max(1)
+            .min(last);
+        (&columns[upper.saturating_sub(1)], &columns[upper])
+    }
+
+    fn factor(&self, age: u8, distance_metres: u32) -> f64 {
+        let factor = |column: &Column| {
+            let index = usize::from(age.saturating_sub(self.youngest_age));
+            column.factors[index.min(column.factors.len() - 1)]
+        };
+        let (lower, upper) = self.columns_around(distance_metres);
+        // Factors aren't extrapolated beyond the table's distances
+        let distance = distance_metres.clamp(lower.distance_metres, upper.distance_metres);
+        interpolate(
+            f64::from(distance).ln(),
+            (f64::from(lower.distance_metres).ln(), factor(lower)),
+            (f64::from(upper.distance_metres).ln(), factor(upper)),
+        )
+    }
+
+    /// Distances between the table's are interpolated on a log-log scale, which follows how pace
+    /// falls off with distance
+    fn open_standard(&self, distance_metres: u32) -> f64 {
+        let (lower, upper) = self.columns_around(distance_metres);
+        let point = |column: &Column| {
+            (
+                f64::from(column.distance_metres).ln(),
+                column.open_standard.ln(),
+            )
+        };
+        let distance = f64::from(distance_metres.max(1)).ln();
+        if lower.distance_metres == upper.distance_metres {
+            // A single distance, scale with Riegel's exponent
+            return lower.open_standard * ((distance - point(lower).0) * 1.06).exp();
+        }
+        interpolate(distance, point(lower), point(upper)).exp()
+    }
+}
+
+impl AgeGradeTable {
+    /// Load tables from CSV, one for each sex. Each table's first row is `Age` followed by the
+    /// distance of each column, in metres or as e.g. `5 km`, `Mile`, `H. Mar` or `Marathon`. The
+    /// next row is `OC` followed by the open standard for each distance, in seconds or as a time.
+    /// Then there is a row for each single year of age, starting with the age followed by the
+    /// factor for each distance. Blank lines and lines starting with `#` are skipped
+    ///
+    /// ```
+    /// use parkrust::age_grade::AgeGradeTable;
+    /// use parkrust::models::parkrun::Sex;
+    ///
+    /// let male = "Age,5 km,10 km\nOC,779,1603\n40,0.9500,0.9510\n41,0.9450,0.9460\n";
+    /// let female = "Age,5 km,10 km\nOC,884,1783\n40,0.9400,0.9420\n41,0.9350,0.9370\n";
+    /// let table = AgeGradeTable::from_csv(male, female)?;
+    /// assert_eq!(table.age_factor(Sex::Female, 41, 10000), 0.937);
+    /// # Ok::<(), parkrust::age_grade::ParseAgeGradeTableError>(())
+    /// ```
+    pub fn from_csv(male: &str, female: &str) -> Result<Self, ParseAgeGradeTableError> {
+        Ok(AgeGradeTable {
+            male: SexTable::from_csv(male)?,
+            female: SexTable::from_csv(female)?,
+        })
+    }
+
+    /// The built in road running tables, embedded as CSV and loaded with
+    /// [`from_csv`](Self::from_csv), see the [module docs](self)
+    pub fn road() -> &'static AgeGradeTable {
+        static TABLE: OnceLock<AgeGradeTable> = OnceLock::new();
+        TABLE.get_or_init(|| {
+            AgeGradeTable::from_csv(MALE_ROAD_TABLE, FEMALE_ROAD_TABLE)
+                .expect("built in age grade tables are valid")
+        })
+    }
+
+    fn table(&self, sex: Sex) -> &SexTable {
+        match sex {
+            Sex::Male => &self.male,
+            Sex::Female => &self.female,
+        }
+    }
+
+    /// The age factor for `sex` at `age` over `distance_metres`, 1.0 at the ages athletes are
+    /// fastest. Ages outside the table use the factor of the nearest age, distances between the
+    /// table's are interpolated
+    pub fn age_factor(&self, sex: Sex, age: u8, distance_metres: u32) -> f64 {
+        self.table(sex).factor(age, distance_metres)
+    }
+
+    /// The best time at any age for `sex` over `distance_metres`
+    pub fn open_standard(&self, sex: Sex, distance_metres: u32) -> Duration {
+        duration(self.table(sex).open_standard(distance_metres))
+    }
+
+    /// The best time possible for `sex` at `age` over `distance_metres`
+    pub fn age_standard(&self, sex: Sex, age: u8, distance_metres: u32) -> Duration {
+        let table = self.table(sex);
+        duration(table.open_standard(distance_metres) / table.factor(age, distance_metres))
+    }
+
+    /// The age grade percentage of `run_time` for `sex` at `age` over `distance_metres`, `None`
+    /// for a zero time
+    pub fn age_grade(
+        &self,
+        sex: Sex,
+        age: u8,
+        distance_metres: u32,
+        run_time: RunTime,
+    ) -> Option<f32> {
+        if run_time.num_seconds() == 0 {
+            return None;
+        }
+        let standard = seconds(self.age_standard(sex, age, distance_metres));
+        Some((standard / f64::from(run_time.num_seconds()) * 100.0) as f32)
+    }
+
+    /// The time an athlete at their fastest age would need to run over `distance_metres` to match
+    /// `run_time`
+    pub fn age_equivalent_time(
+        &self,
+        sex: Sex,
+        age: u8,
+        distance_metres: u32,
+        run_time: RunTime,
+    ) -> RunTime {
+        let seconds =
+            f64::from(run_time.num_seconds()) * self.age_factor(sex, age, distance_metres);
+        RunTime::from_seconds(seconds.round() as u32)
+    }
+
+    /// The time needed for an age grade of `percentage`
+    pub fn time_for_age_grade(
+        &self,
+        sex: Sex,
+        age: u8,
+        distance_metres: u32,
+        percentage: f32,
+    ) -> RunTime {
+        let standard = seconds(self.age_standard(sex, age, distance_metres));
+        RunTime::from_seconds((standard * 100.0 / f64::from(percentage)).round() as u32)
+    }
+
+    /// The range of age grades `run_time` could have across the ages in `category`, to check an
+    /// age grade from the API when only the category is known. `None` for wheelchair categories,
+    /// which aren't age graded this way
+    pub fn age_grade_range(
+        &self,
+        category: AgeCategory,
+        distance_metres: u32,
+        run_time: RunTime,
+    ) -> Option<RangeInclusive<f32>> {
+        if category.class == AgeClass::Wheelchair {
+            return None;
+        }
+        // The youngest juniors e.g. JM10 have no lower bound, start at the youngest junior age
+        let min_age = category.min_age.unwrap_or(YOUNGEST_JUNIOR_AGE);
+        // Open ended categories such as VM100+ are graded at their youngest age and the next few
+        // years
+        let max_age = category.max_age.unwrap_or(min_age.saturating_add(4));
+        let grades = (min_age..=max_age)
+            .map(|age| self.age_grade(category.sex, age, distance_metres, run_time))
+            .collect::<Option<Vec<f32>>>()?;
+        let lowest = grades.iter().copied().fold(f32::INFINITY, f32::min);
+        let highest = grades.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        Some(lowest..=highest)
+    }
+}
+
+/// The age factor for `sex` at `age` over `distance_metres` from the [built in
+/// tables](AgeGradeTable::road)
+pub fn age_factor(sex: Sex, age: u8, distance_metres: u32) -> f64 {
+    AgeGradeTable::road().age_factor(sex, age, distance_metres)
+}
+
+/// See [`AgeGradeTable::open_standard`]
+pub fn open_standard(sex: Sex, distance_metres: u32) -> Duration {
+    AgeGradeTable::road().open_standard(sex, distance_metres)
+}
+
+/// See [`AgeGradeTable::age_standard`]
+pub fn age_standard(sex: Sex, age: u8, distance_metres: u32) -> Duration {
+    AgeGradeTable::road().age_standard(sex, age, distance_metres)
+}
+
+/// See [`AgeGradeTable::age_grade`]
+pub fn age_grade(sex: Sex, age: u8, distance_metres: u32, run_time: RunTime) -> Option<f32> {
+    AgeGradeTable::road().age_grade(sex, age, distance_metres, run_time)
+}
+
+/// See [`AgeGradeTable::age_equivalent_time`]
+pub fn age_equivalent_time(sex: Sex, age: u8, distance_metres: u32, run_time: RunTime) -> RunTime {
+    AgeGradeTable::road().age_equivalent_time(sex, age, distance_metres, run_time)
+}
+
+/// See [`AgeGradeTable::time_for_age_grade`]
+pub fn time_for_age_grade(sex: Sex, age: u8, distance_metres: u32, percentage: f32) -> RunTime {
+    AgeGradeTable::road().time_for_age_grade(sex, age, distance_metres, percentage)
+}
+
+/// See [`AgeGradeTable::age_grade_range`]
+pub fn age_grade_range(
+    category: AgeCategory,
+    distance_metres: u32,
+    run_time: RunTime,
+) -> Option<RangeInclusive<f32>> {
+    AgeGradeTable::road().age_grade_range(category, distance_metres, run_time)
+}
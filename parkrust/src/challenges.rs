@@ -0,0 +1,303 @@
+//! Unofficial challenges parkrun tourists set themselves, worked out from an athlete's results.
+//!
+//! Challenges which depend on event names or locations also need the [`Event`]s the results were
+//! run at. Results at events missing from the list are left out of those challenges.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+use crate::client::requests::chronological;
+use crate::models::parkrun::{Country, Event, RunResult};
+
+fn events_by_number(events: &[Event]) -> HashMap<&str, &Event> {
+    events
+        .iter()
+        .map(|event| (event.event_number.as_str(), event))
+        .collect()
+}
+
+/// Events run at, oldest first, each only once. Results are taken oldest first throughout so
+/// challenges credit the first time something was done
+fn events_run<'a>(results: &[RunResult], events: &'a [Event]) -> Vec<&'a Event> {
+    let events = events_by_number(events);
+    let mut seen = BTreeSet::new();
+    chronological(results)
+        .into_iter()
+        .filter(|result| seen.insert(result.event_number.as_str()))
+        .filter_map(|result| events.get(result.event_number.as_str()).copied())
+        .collect()
+}
+
+/// The parkrun alphabet, running at an event starting with each letter
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Alphabet {
+    /// The short name of the first event run at for each letter
+    pub letters: BTreeMap<char, String>,
+}
+
+impl Alphabet {
+    pub fn count(&self) -> usize {
+        self.letters.len()
+    }
+
+    pub fn missing(&self) -> Vec<char> {
+        ('A'..='Z')
+            .filter(|letter| !self.letters.contains_key(letter))
+            .collect()
+    }
+}
+
+/// The letter of `A` to `Z` a character is a variant of, ignoring case and diacritics e.g. `Å` is
+/// `A`. `None` for characters which aren't Latin letters
+fn base_letter(character: char) -> Option<char> {
+    let letter = match character.to_lowercase().next()? {
+        letter @ 'a'..='z' => letter,
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'æ' | 'ā' | 'ă' | 'ą' => 'a',
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => 'c',
+        'ď' | 'đ' | 'ð' => 'd',
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => 'e',
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => 'g',
+        'ĥ' | 'ħ' => 'h',
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => 'i',
+        'ĵ' => 'j',
+        'ķ' => 'k',
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => 'l',
+        'ñ' | 'ń' | 'ņ' | 'ň' => 'n',
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' | 'œ' => 'o',
+        'ŕ' | 'ŗ' | 'ř' => 'r',
+        'ś' | 'ŝ' | 'ş' | 'š' | 'ș' | 'ß' => 's',
+        'ţ' | 'ť' | 'ŧ' | 'ț' | 'þ' => 't',
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => 'u',
+        'ŵ' => 'w',
+        'ý' | 'ÿ' | 'ŷ' => 'y',
+        'ź' | 'ż' | 'ž' => 'z',
+        _ => return None,
+    };
+    Some(letter.to_ascii_uppercase())
+}
+
+/// Events count for the letter their name starts with, ignoring diacritics so Ålesund counts for
+/// A. Events whose names don't start with a letter don't count
+pub fn alphabet(results: &[RunResult], events: &[Event]) -> Alphabet {
+    let mut letters = BTreeMap::new();
+    for event in events_run(results, events) {
+        let first = event.event_short_name.trim_start().chars().next();
+        if let Some(letter) = first.and_then(base_letter) {
+            letters
+                .entry(letter)
+                .or_insert_with(|| event.event_short_name.clone());
+        }
+    }
+    Alphabet { letters }
+}
+
+/// The Wilson index, the highest `n` where the athlete has run every run number from 1 to `n`, at
+/// any events
+pub fn wilson_index(results: &[RunResult]) -> u32 {
+    let run_numbers = results
+        .iter()
+        .filter_map(|result| result.run_id.parse::<u32>().ok())
+        .collect::<BTreeSet<u32>>();
+    (1..).take_while(|run| run_numbers.contains(run)).count() as u32
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CompassPoint {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl CompassPoint {
+    pub const ALL: [CompassPoint; 4] = [
+        CompassPoint::North,
+        CompassPoint::South,
+        CompassPoint::East,
+        CompassPoint::West,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompassPoint::North => "North",
+            CompassPoint::South => "South",
+            CompassPoint::East => "East",
+            CompassPoint::West => "West",
+        }
+    }
+}
+
+impl fmt::Display for CompassPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The compass challenge, running at events with north, south, east and west in their names
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Compass {
+    /// The short name of the first event run at for each point
+    pub points: BTreeMap<CompassPoint, String>,
+}
+
+impl Compass {
+    pub fn is_complete(&self) -> bool {
+        self.points.len() == CompassPoint::ALL.len()
+    }
+
+    pub fn missing(&self) -> Vec<CompassPoint> {
+        CompassPoint::ALL
+            .into_iter()
+            .filter(|point| !self.points.contains_key(point))
+            .collect()
+    }
+}
+
+/// Names match anywhere and ignoring case, so Northala and Eastleigh count
+pub fn compass(results: &[RunResult], events: &[Event]) -> Compass {
+    let mut points = BTreeMap::new();
+    for event in events_run(results, events) {
+        let name = event.event_short_name.to_lowercase();
+        for point in CompassPoint::ALL {
+            if name.contains(&point.as_str().to_lowercase()) {
+                points
+                    .entry(point)
+                    .or_insert_with(|| event.event_short_name.clone());
+            }
+        }
+    }
+    Compass { points }
+}
+
+/// Stopwatch bingo, finishing with every seconds value from 00 to 59
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Bingo {
+    pub seconds: BTreeSet<u32>,
+}
+
+impl Bingo {
+    pub fn is_complete(&self) -> bool {
+        self.seconds.len() == 60
+    }
+
+    pub fn missing(&self) -> Vec<u32> {
+        (0..60)
+            .filter(|second| !self.seconds.contains(second))
+            .collect()
+    }
+}
+
+pub fn bingo(results: &[RunResult]) -> Bingo {
+    Bingo {
+        seconds: results
+            .iter()
+            .map(|result| result.run_time.seconds())
+            .collect(),
+    }
+}
+
+/// The stopwatch challenge, finishing in every whole minute between the fastest and slowest times
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Stopwatch {
+    /// Minutes finished in, counting hours as 60 minutes
+    pub minutes: BTreeSet<u32>,
+}
+
+impl Stopwatch {
+    /// Minutes between the fastest and slowest finishes that haven't been finished in
+    pub fn missing(&self) -> Vec<u32> {
+        let (Some(fastest), Some(slowest)) = (self.minutes.first(), self.minutes.last()) else {
+            return Vec::new();
+        };
+        (*fastest..=*slowest)
+            .filter(|minute| !self.minutes.contains(minute))
+            .collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        !self.minutes.is_empty() && self.missing().is_empty()
+    }
+}
+
+pub fn stopwatch(results: &[RunResult]) -> Stopwatch {
+    Stopwatch {
+        minutes: results
+            .iter()
+            .map(|result| result.run_time.num_seconds() / 60)
+            .collect(),
+    }
+}
+
+/// Progress towards running every event in a region
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Regionnaire {
+    /// Event short names, in the order the events were given
+    pub completed: Vec<String>,
+    pub remaining: Vec<String>,
+}
+
+impl Regionnaire {
+    pub fn is_complete(&self) -> bool {
+        self.remaining.is_empty() && !self.completed.is_empty()
+    }
+}
+
+/// Progress towards running every event in `region`, such as every event in a country. Events
+/// which are no longer live only count once they've been run
+pub fn regionnaire<'a>(
+    results: &[RunResult],
+    region: impl IntoIterator<Item = &'a Event>,
+) -> Regionnaire {
+    let run = results
+        .iter()
+        .map(|result| result.event_number.as_str())
+        .collect::<BTreeSet<&str>>();
+    let (completed, remaining) = region
+        .into_iter()
+        .filter(|event| event.is_live() || run.contains(event.event_number.as_str()))
+        .partition::<Vec<&Event>, _>(|event| run.contains(event.event_number.as_str()));
+    let names = |events: Vec<&Event>| {
+        events
+            .into_iter()
+            .map(|event| event.event_short_name.clone())
+            .collect()
+    };
+    Regionnaire {
+        completed: names(completed),
+        remaining: names(remaining),
+    }
+}
+
+/// Regionnaire progress for the events in `country`
+pub fn country_regionnaire(
+    results: &[RunResult],
+    events: &[Event],
+    country: Country,
+) -> Regionnaire {
+    regionnaire(
+        results,
+        events.iter().filter(|event| event.country == country),
+    )
+}
+
+/// Every challenge, computed once so they can be shared between views
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Challenges {
+    pub alphabet: Alphabet,
+    pub wilson_index: u32,
+    pub compass: Compass,
+    pub bingo: Bingo,
+    pub stopwatch: Stopwatch,
+}
+
+impl Challenges {
+    pub fn from_results(results: &[RunResult], events: &[Event]) -> Self {
+        Challenges {
+            alphabet: alphabet(results, events),
+            wilson_index: wilson_index(results),
+            compass: compass(results, events),
+            bingo: bingo(results),
+            stopwatch: stopwatch(results),
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use reqwest::Url;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration as StdDuration;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::client::Cassette;
+use crate::client::{api_root, get_base_url, ClientCredentials, ParkrunClient, DEFAULT_MAX_PAGES};
+use crate::Result;
+
+/// Configure a [`ParkrunClient`], e.g. to point it at a proxy or a local mock server
+///
+/// ```no_run
+/// # fn main() -> parkrust::Result<()> {
+/// use parkrust::client::ParkrunClient;
+///
+/// let client = ParkrunClient::builder()
+///     .base_url("http://localhost:8080")
+///     .user_agent("my-app/1.0")
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct ParkrunClientBuilder {
+    base_url: Option<String>,
+    credentials: Option<ClientCredentials>,
+    user_agent: Option<String>,
+    max_pages: Option<usize>,
+    request_client: Option<reqwest::Client>,
+    #[cfg(not(target_arch = "wasm32"))]
+    timeout: Option<StdDuration>,
+    #[cfg(not(target_arch = "wasm32"))]
+    proxy: Option<String>,
+    #[cfg(not(target_arch = "wasm32"))]
+    cassette: Option<Cassette>,
+}
+
+impl ParkrunClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The URL of the parkrun API, defaults to `https://api.parkrun.com`. Any path is kept, so the
+    /// API can be reached through a proxy at e.g. `http://localhost:8080/parkrun/`
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// The client id and secret used to authenticate the app with the API
+    pub fn credentials(
+        mut self,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+    ) -> Self {
+        self.credentials = Some(ClientCredentials {
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+        });
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Stop following next page links after `max_pages` pages, defaults to
+    /// [`DEFAULT_MAX_PAGES`]
+    pub fn max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = Some(max_pages);
+        self
+    }
+
+    /// Use a preconfigured `reqwest::Client`. The `timeout` and `proxy` options only apply to the
+    /// default client, so [`build`](Self::build) fails if either is also set
+    pub fn request_client(mut self, request_client: reqwest::Client) -> Self {
+        self.request_client = Some(request_client);
+        self
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn timeout(mut self, timeout: StdDuration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Send all requests through the proxy at `proxy_url`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn proxy(mut self, proxy_url: impl Into<String>) -> Self {
+        self.proxy = Some(proxy_url.into());
+        self
+    }
+
+    /// Record requests to, or replay them from, `cassette`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn cassette(mut self, cassette: Cassette) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Fails with [`Error::InvalidConfig`](crate::Error::InvalidConfig) when a `request_client`
+    /// is set along with a `timeout` or `proxy`, which would otherwise be ignored
+    pub fn build(self) -> Result<ParkrunClient> {
+        let base_url = match &self.base_url {
+            Some(base_url) => api_root(&Url::parse(base_url)?),
+            None => get_base_url(),
+        };
+
+        let request_client = match self.request_client {
+            #[cfg(not(target_arch = "wasm32"))]
+            Some(_) if self.timeout.is_some() || self.proxy.is_some() => {
+                return Err(crate::Error::InvalidConfig(String::from(
+                    "timeout and proxy can't be set along with a request client, configure them \
+                     on the request client instead",
+                )));
+            }
+            Some(request_client) => request_client,
+            None => {
+                #[allow(unused_mut)]
+                let mut builder = reqwest::Client::builder();
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    if let Some(timeout) = self.timeout {
+                        builder = builder.timeout(timeout);
+                    }
+                    if let Some(proxy) = &self.proxy {
+                        builder = builder.proxy(reqwest::Proxy::all(Url::parse(proxy)?)?);
+                    }
+                }
+                builder.build()?
+            }
+        };
+
+        Ok(ParkrunClient {
+            base_url,
+            request_client,
+            credentials: self.credentials.unwrap_or_default(),
+            user_agent: self.user_agent,
+            max_pages: self.max_pages.unwrap_or(DEFAULT_MAX_PAGES),
+            #[cfg(not(target_arch = "wasm32"))]
+            cassette: self.cassette.map(Arc::new),
+        })
+    }
+}
@@ -0,0 +1,262 @@
+use reqwest::{Request, Response, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::client::redact::{redact_form, redact_json, redact_pair};
+use crate::client::rewrite_query;
+use crate::{Error, Result};
+
+/// Whether a [`Cassette`] is recording requests to the API or replaying them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Send requests to the API and write each request and response to the cassette file
+    Record,
+    /// Serve requests from the cassette file without touching the network
+    Replay,
+}
+
+/// A file of recorded API requests and responses, used to test against real payloads offline.
+/// Tokens and passwords are redacted before anything is written
+///
+/// ```no_run
+/// # async fn example() -> parkrust::Result<()> {
+/// use parkrust::client::{Cassette, ParkrunClient};
+///
+/// // Record once against the live API
+/// let client = ParkrunClient::builder()
+///     .cassette(Cassette::record("tests/cassettes/me.json"))
+///     .build()?;
+/// client.authenticate("A123456", "password").await?.get_me().await?;
+///
+/// // Then replay the same calls offline
+/// let client = ParkrunClient::builder()
+///     .cassette(Cassette::replay("tests/cassettes/me.json")?)
+///     .build()?;
+/// client.authenticate("A123456", "password").await?.get_me().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    state: Mutex<CassetteState>,
+}
+
+#[derive(Debug, Default)]
+struct CassetteState {
+    interactions: Vec<Interaction>,
+    /// Whether each interaction has been replayed, so repeated requests get successive responses
+    replayed: Vec<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    /// Path and query, relative to the base URL so cassettes can be replayed against any server
+    url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    /// JSON bodies are stored as JSON so cassettes are readable, anything else as a string
+    body: Value,
+}
+
+impl Cassette {
+    /// Record requests to `path`, replacing anything already recorded there
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Cassette {
+            path: path.into(),
+            mode: CassetteMode::Record,
+            state: Mutex::new(CassetteState::default()),
+        }
+    }
+
+    /// Replay requests previously recorded to `path`
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let contents = fs::read_to_string(&path)?;
+        let file: CassetteFile =
+            serde_json::from_str(&contents).map_err(|source| Error::Decode {
+                source,
+                payload: contents,
+            })?;
+        Ok(Cassette {
+            path,
+            mode: CassetteMode::Replay,
+            state: Mutex::new(CassetteState {
+                replayed: vec![false; file.interactions.len()],
+                interactions: file.interactions,
+            }),
+        })
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Send `request` with `request_client`, or serve it from the cassette when replaying
+    pub(crate) async fn execute(
+        &self,
+        request_client: &reqwest::Client,
+        base_url: &Url,
+        request: Request,
+    ) -> Result<Response> {
+        let recorded_request = record_request(base_url, &request);
+        match self.mode {
+            CassetteMode::Replay => self.replay_request(&recorded_request),
+            CassetteMode::Record => {
+                let response = request_client.execute(request).await?;
+                let (recorded_response, body) = record_response(response).await?;
+                // The client gets the original body, only the recording is redacted
+                let response = build_response(&recorded_response, body)?;
+                self.save_interaction(Interaction {
+                    request: recorded_request,
+                    response: recorded_response,
+                })?;
+                Ok(response)
+            }
+        }
+    }
+
+    /// Serve the first unplayed interaction matching the request's method and URL
+    fn replay_request(&self, request: &RecordedRequest) -> Result<Response> {
+        let mut state = self.state.lock().unwrap();
+        let CassetteState {
+            interactions,
+            replayed,
+        } = &mut *state;
+        let index = interactions
+            .iter()
+            .zip(replayed.iter())
+            .position(|(interaction, replayed)| {
+                !replayed
+                    && interaction.request.method == request.method
+                    && interaction.request.url == request.url
+            })
+            .ok_or_else(|| {
+                Error::Cassette(format!(
+                    "no recorded response for {} {} in {}",
+                    request.method,
+                    request.url,
+                    self.path.display()
+                ))
+            })?;
+        replayed[index] = true;
+        let response = &interactions[index].response;
+        build_response(response, response.body_text())
+    }
+
+    /// Add an interaction and rewrite the cassette file, so nothing is lost if the test panics
+    fn save_interaction(&self, interaction: Interaction) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.interactions.push(interaction);
+        state.replayed.push(false);
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = CassetteFile {
+            interactions: state.interactions.clone(),
+        };
+        let contents = serde_json::to_string_pretty(&file)
+            .map_err(|err| Error::Cassette(format!("failed to serialize cassette: {err}")))?;
+        fs::write(&self.path, contents)?;
+        Ok(())
+    }
+}
+
+fn record_request(base_url: &Url, request: &Request) -> RecordedRequest {
+    let mut url = request.url().clone();
+    rewrite_query(&mut url, |(key, value)| Some(redact_pair(key, value)));
+    let url = match base_url.make_relative(&url) {
+        Some(relative) => format!("/{}", relative.trim_start_matches("./")),
+        None => url.to_string(),
+    };
+
+    let body = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .map(redact_form);
+
+    RecordedRequest {
+        method: request.method().to_string(),
+        url,
+        body,
+    }
+}
+
+/// Record a response, returning the recording and the original body
+async fn record_response(response: Response) -> Result<(RecordedResponse, String)> {
+    let status = response.status().as_u16();
+    // Redaction changes the length of the body
+    let headers = response
+        .headers()
+        .iter()
+        .filter(|(name, _)| {
+            !["set-cookie", "content-length", "transfer-encoding"].contains(&name.as_str())
+        })
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+    let text = response.text().await?;
+    let body = match serde_json::from_str::<Value>(&text) {
+        Ok(mut json) => {
+            redact_json(&mut json);
+            json
+        }
+        Err(_) => Value::String(text.clone()),
+    };
+    Ok((
+        RecordedResponse {
+            status,
+            headers,
+            body,
+        },
+        text,
+    ))
+}
+
+impl RecordedResponse {
+    fn body_text(&self) -> String {
+        match &self.body {
+            Value::String(text) => text.clone(),
+            json => json.to_string(),
+        }
+    }
+}
+
+fn build_response(recorded: &RecordedResponse, body: String) -> Result<Response> {
+    let mut builder = http::Response::builder().status(recorded.status);
+    for (name, value) in &recorded.headers {
+        builder = builder.header(name, value);
+    }
+    let response = builder
+        .body(body)
+        .map_err(|err| Error::Cassette(format!("invalid recorded response: {err}")))?;
+    Ok(Response::from(response))
+}
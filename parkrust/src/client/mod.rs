@@ -1,46 +1,128 @@
 use chrono::{DateTime, Duration, Utc};
-use reqwest::{Method, RequestBuilder, Response, Url};
+use reqwest::{
+    header::{RETRY_AFTER, USER_AGENT},
+    Method, Request, RequestBuilder, Response, StatusCode, Url,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
 
-use crate::models::parkrun::{AuthResponse, RefreshTokenResponse};
+use crate::models::parkrun::{
+    normalize_athlete_id, Athlete, AuthResponse, Club, ListAthletes, ListClubs, ListResponse,
+    RefreshTokenResponse,
+};
+use crate::{Error, Result};
 
+mod builder;
+#[cfg(not(target_arch = "wasm32"))]
+mod cassette;
+pub(crate) mod pagination;
+mod redact;
 pub mod requests;
+mod token_store;
 
-#[derive(Clone, PartialEq, Eq)]
+pub use builder::ParkrunClientBuilder;
+#[cfg(not(target_arch = "wasm32"))]
+pub use cassette::{Cassette, CassetteMode};
+pub use pagination::DEFAULT_MAX_PAGES;
+#[cfg(not(target_arch = "wasm32"))]
+pub use token_store::FileTokenStore;
+pub use token_store::{MemoryTokenStore, TokenStore};
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Token {
     pub access_token: String,
     pub refresh_token: String,
     pub expires_at: DateTime<Utc>,
 }
 
+/// The client id and secret the app authenticates with, defaults to those of the parkrun app
+#[derive(Clone, PartialEq, Eq)]
+pub struct ClientCredentials {
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+#[derive(Clone)]
 pub struct ParkrunClient {
     pub base_url: Url,
     pub request_client: reqwest::Client,
+    pub credentials: ClientCredentials,
+    pub user_agent: Option<String>,
+    /// Most pages [`Listable::stream`](crate::models::parkrun::Listable::stream) and
+    /// [`Listable::list`](crate::models::parkrun::Listable::list) will fetch before giving up
+    pub max_pages: usize,
+    /// Record requests to, or replay them from, a cassette file instead of only using the network
+    #[cfg(not(target_arch = "wasm32"))]
+    pub cassette: Option<Arc<Cassette>>,
 }
 
+/// Called with the new token whenever the client refreshes its token
+pub type TokenRefreshCallback = dyn Fn(&Token) + Send + Sync;
+
 pub struct AuthenticatedParkrunClient {
-    pub base_url: Url,
-    pub request_client: reqwest::Client,
+    pub client: ParkrunClient,
     pub token: Token,
+    pub token_store: Option<Arc<dyn TokenStore>>,
+    pub on_token_refresh: Option<Arc<TokenRefreshCallback>>,
 }
 
 #[cfg(target_arch = "wasm32")]
-fn get_base_url() -> Url {
+pub(crate) fn get_base_url() -> Url {
     Url::parse("https://parkrun-proxy.x2.workers.dev/").unwrap()
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-fn get_base_url() -> Url {
-    Url::parse("https://api.parkrun.com").unwrap()
+pub(crate) fn get_base_url() -> Url {
+    Url::parse("https://api.parkrun.com/").unwrap()
+}
+
+/// Resolve an API path such as `/v1/results` against `base_url`. Paths are relative to the base
+/// URL, so a base URL with a path e.g. a proxy at `http://localhost/parkrun/` keeps its path
+pub(crate) fn api_url(base_url: &Url, path: &str) -> Result<Url> {
+    Ok(api_root(base_url).join(path.trim_start_matches('/'))?)
+}
+
+/// Percent-encode `segment` for use as a single segment of an API path, so values such as ids can't
+/// add segments or a query to the path
+pub(crate) fn encode_path_segment(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                char::from(byte).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// The base URL as a directory, so relative paths are joined onto the end of its path
+pub(crate) fn api_root(base_url: &Url) -> Url {
+    let mut base_url = base_url.clone();
+    if !base_url.path().ends_with('/') {
+        base_url.set_path(&format!("{}/", base_url.path()));
+    }
+    base_url
 }
 
 impl Token {
-    pub fn from_auth_response(response: AuthResponse) -> Self {
-        Token {
+    /// Build a token from an auth response, the API sends `expires_in` as a string of seconds
+    pub fn from_auth_response(response: AuthResponse) -> Result<Self> {
+        let expires_in = response
+            .expires_in
+            .parse::<i64>()
+            .map_err(|err| Error::Decode {
+                source: serde::de::Error::custom(format!("invalid expires_in: {err}")),
+                payload: response.expires_in.clone(),
+            })?;
+        Ok(Token {
             access_token: response.access_token,
             refresh_token: response.refresh_token,
-            expires_at: Utc::now() + Duration::seconds(response.expires_in.parse().unwrap()),
-        }
+            expires_at: Utc::now() + Duration::seconds(expires_in),
+        })
     }
 
     pub fn is_expired(&self) -> bool {
@@ -48,6 +130,35 @@ impl Token {
     }
 }
 
+/// Tokens are secrets so are never included in debug output
+impl fmt::Debug for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Token")
+            .field("access_token", &"<redacted>")
+            .field("refresh_token", &"<redacted>")
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
+
+impl Default for ClientCredentials {
+    fn default() -> Self {
+        ClientCredentials {
+            client_id: String::from("netdreams-iphone-s01"),
+            client_secret: String::from("gfKbDD6NJkYoFmkisR(iVFopQCKWzbQeQgZAZZKK"),
+        }
+    }
+}
+
+impl fmt::Debug for ClientCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientCredentials")
+            .field("client_id", &self.client_id)
+            .field("client_secret", &"<redacted>")
+            .finish()
+    }
+}
+
 impl Default for ParkrunClient {
     fn default() -> Self {
         Self::new()
@@ -59,28 +170,64 @@ impl ParkrunClient {
         ParkrunClient {
             base_url: get_base_url(),
             request_client: reqwest::Client::builder().build().unwrap(),
+            credentials: ClientCredentials::default(),
+            user_agent: None,
+            max_pages: DEFAULT_MAX_PAGES,
+            #[cfg(not(target_arch = "wasm32"))]
+            cassette: None,
         }
     }
 
-    pub fn request(&self, method: Method, path: &str) -> RequestBuilder {
-        self.request_client
-            .request(method, self.base_url.join(path).unwrap())
-            .basic_auth(
-                "netdreams-iphone-s01",
-                Some("gfKbDD6NJkYoFmkisR(iVFopQCKWzbQeQgZAZZKK"),
-            )
-            .header("X-Powered-By", "Park Rust")
+    pub fn builder() -> ParkrunClientBuilder {
+        ParkrunClientBuilder::new()
+    }
+
+    /// Create an authenticated client from a previously saved token
+    pub fn with_token(self, token: Token) -> AuthenticatedParkrunClient {
+        AuthenticatedParkrunClient {
+            client: self,
+            token,
+            token_store: None,
+            on_token_refresh: None,
+        }
+    }
+
+    /// Build a request to the API without any authentication
+    fn unauthenticated_request(&self, method: Method, path: &str) -> Result<RequestBuilder> {
+        let mut request = self
+            .request_client
+            .request(method, api_url(&self.base_url, path)?)
+            .header("X-Powered-By", "Park Rust");
+        if let Some(user_agent) = &self.user_agent {
+            request = request.header(USER_AGENT, user_agent);
+        }
+        Ok(request)
+    }
+
+    pub fn request(&self, method: Method, path: &str) -> Result<RequestBuilder> {
+        Ok(self.unauthenticated_request(method, path)?.basic_auth(
+            &self.credentials.client_id,
+            Some(&self.credentials.client_secret),
+        ))
+    }
+
+    /// Send a request, through the cassette if one is attached
+    pub(crate) async fn execute(&self, request: Request) -> Result<Response> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(cassette) = &self.cassette {
+            return cassette
+                .execute(&self.request_client, &self.base_url, request)
+                .await;
+        }
+        Ok(self.request_client.execute(request).await?)
     }
 
     pub async fn authenticate(
         self,
-        mut athlete_id: &str,
+        athlete_id: &str,
         password: &str,
-    ) -> Result<AuthenticatedParkrunClient, Box<dyn std::error::Error>> {
-        if athlete_id.starts_with('A') || athlete_id.starts_with('a') {
-            athlete_id = &athlete_id[1..athlete_id.len()]
-        }
-
+    ) -> Result<AuthenticatedParkrunClient> {
+        let athlete_id = normalize_athlete_id(athlete_id);
         let body = HashMap::from([
             ("username", athlete_id),
             ("password", password),
@@ -88,42 +235,37 @@ impl ParkrunClient {
             ("grant_type", "password"),
         ]);
 
-        // TODO Handle possible errors here
-        let response = self
-            .request(Method::POST, "/user_auth.php")
+        let request = self
+            .request(Method::POST, "/user_auth.php")?
             .form(&body)
             .header("Content-Type", "application/x-www-form-urlencoded")
-            .send()
-            .await?
-            .json::<AuthResponse>()
-            .await?;
+            .build()?;
+        let response = self.execute(request).await?;
+        if is_auth_failure(response.status()) {
+            return Err(Error::InvalidCredentials);
+        }
+        let response = parse_response::<AuthResponse>(response).await?;
 
-        Ok(AuthenticatedParkrunClient {
-            base_url: self.base_url,
-            request_client: self.request_client,
-            token: Token::from_auth_response(response),
-        })
+        Ok(self.with_token(Token::from_auth_response(response)?))
     }
 
-    pub async fn refresh_token(
-        &mut self,
-        refresh_token: &str,
-    ) -> Result<Token, Box<dyn std::error::Error>> {
+    pub async fn refresh_token(&mut self, refresh_token: &str) -> Result<Token> {
         let body = HashMap::from([
             ("refresh_token", refresh_token),
             ("grant_type", "refresh_token"),
         ]);
 
-        let response = self
-            .request(Method::POST, "/auth/refresh")
+        let request = self
+            .request(Method::POST, "/auth/refresh")?
             .form(&body)
             .header("Content-Type", "application/x-www-form-urlencoded")
-            .send()
-            .await?
-            .json::<RefreshTokenResponse>()
-            .await?;
+            .build()?;
+        let response = self.execute(request).await?;
+        if is_auth_failure(response.status()) {
+            return Err(Error::InvalidRefreshToken);
+        }
+        let response = parse_response::<RefreshTokenResponse>(response).await?;
 
-        println!("Refresh token response is: {response:?}");
         Ok(Token {
             expires_at: Utc::now() + Duration::seconds(response.expires_in.into()),
             access_token: response.access_token,
@@ -134,48 +276,168 @@ impl ParkrunClient {
 
 impl AuthenticatedParkrunClient {
     pub fn new(token: Token) -> Self {
-        AuthenticatedParkrunClient {
-            base_url: get_base_url(),
-            request_client: reqwest::Client::builder().build().unwrap(),
-            token,
-        }
+        ParkrunClient::new().with_token(token)
     }
 
-    pub fn request(&self, method: Method, path: &str) -> RequestBuilder {
-        let request_url = self.base_url.join(path).unwrap();
-        self.request_client
-            .request(method, request_url)
-            .header("X-Powered-By", "Park Rust")
-            .query(&[("access_token", self.token.access_token.clone())])
+    /// Create a client from the token saved in `token_store`, returns `None` if no token has been
+    /// saved. Refreshed tokens are saved back to the store
+    pub fn from_token_store(token_store: impl TokenStore + 'static) -> Result<Option<Self>> {
+        let token_store: Arc<dyn TokenStore> = Arc::new(token_store);
+        Ok(token_store.load()?.map(|token| AuthenticatedParkrunClient {
+            token_store: Some(token_store),
+            ..Self::new(token)
+        }))
     }
 
-    pub async fn send_request_with_refresh(
-        &mut self,
-        request: RequestBuilder,
-    ) -> Result<Response, reqwest::Error> {
+    /// Save the current token to `token_store`, and save any refreshed tokens to it from now on
+    pub fn with_token_store(mut self, token_store: impl TokenStore + 'static) -> Result<Self> {
+        token_store.save(&self.token)?;
+        self.token_store = Some(Arc::new(token_store));
+        Ok(self)
+    }
+
+    /// Call `callback` with the new token whenever the token is refreshed, after it has been saved
+    /// to the token store
+    pub fn on_token_refresh(mut self, callback: impl Fn(&Token) + Send + Sync + 'static) -> Self {
+        self.on_token_refresh = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn request(&self, method: Method, path: &str) -> Result<RequestBuilder> {
+        Ok(self
+            .client
+            .unauthenticated_request(method, path)?
+            .query(&[("access_token", self.token.access_token.clone())]))
+    }
+
+    /// Send a request, refreshing the token first if it has expired. If the API still rejects the
+    /// token the token is refreshed and the request is retried once
+    pub async fn send_request_with_refresh(&mut self, request: RequestBuilder) -> Result<Response> {
+        self.send_with_refresh(request.build()?).await
+    }
+
+    /// [`send_request_with_refresh`](Self::send_request_with_refresh) for an already built request
+    pub(crate) async fn send_with_refresh(&mut self, request: Request) -> Result<Response> {
         if self.token.is_expired() {
-            self.refresh_token().await.expect("Failed to refresh token");
+            self.refresh_token().await?;
         }
 
-        request.send().await
+        let retry_request = request.try_clone();
+        let response = self.execute_with_token(request).await?;
+        match retry_request {
+            Some(retry_request) if response.status() == StatusCode::UNAUTHORIZED => {
+                self.refresh_token().await?;
+                self.execute_with_token(retry_request).await
+            }
+            _ => Ok(response),
+        }
     }
 
-    pub async fn refresh_token(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        self.token = ParkrunClient::new()
-            .refresh_token(&self.token.refresh_token)
-            .await?;
+    /// Send a request with the current access token, replacing the token it was built with in case
+    /// the token has since been refreshed
+    async fn execute_with_token(&self, mut request: Request) -> Result<Response> {
+        set_access_token(request.url_mut(), &self.token.access_token);
+        self.client.execute(request).await
+    }
+
+    /// Refresh the access token, saving it to the token store and notifying the refresh callback
+    pub async fn refresh_token(&mut self) -> Result<()> {
+        self.token = self.client.refresh_token(&self.token.refresh_token).await?;
+        if let Some(token_store) = &self.token_store {
+            token_store.save(&self.token)?;
+        }
+        if let Some(on_token_refresh) = &self.on_token_refresh {
+            on_token_refresh(&self.token);
+        }
         Ok(())
     }
 
-    // pub async fn get_me(&self) -> Result<Athlete, Box<dyn std::error::Error>> {
-    //     let athletes = self
-    //         .request(Method::GET, "/v1/me")
-    //         .send()
-    //         .await?
-    //         .json::<ListResponse<ListAthletes>>()
-    //         .await?
-    //         .data
-    //         .athletes;
-    //     Ok(athletes.get(0).unwrap().clone())
-    // }
+    /// Get the profile of the authenticated athlete
+    pub async fn get_me(&mut self) -> Result<Athlete> {
+        self.get_athlete("/v1/me").await
+    }
+
+    pub(crate) async fn get_club(&mut self, path: &str) -> Result<Club> {
+        let request = self.request(Method::GET, path)?;
+        let response = self.send_request_with_refresh(request).await?;
+        parse_response::<ListResponse<ListClubs>>(response)
+            .await?
+            .data
+            .clubs
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::NotFound(format!("club at {path}")))
+    }
+
+    pub(crate) async fn get_athlete(&mut self, path: &str) -> Result<Athlete> {
+        let request = self.request(Method::GET, path)?;
+        let response = self.send_request_with_refresh(request).await?;
+        parse_response::<ListResponse<ListAthletes>>(response)
+            .await?
+            .data
+            .athletes
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::NotFound(format!("athlete at {path}")))
+    }
+}
+
+/// Rewrite each query parameter of `url`, dropping those `rewrite` returns `None` for. The query is
+/// removed entirely when no parameters are left
+pub(crate) fn rewrite_query(
+    url: &mut Url,
+    rewrite: impl FnMut((String, String)) -> Option<(String, String)>,
+) {
+    let query = url
+        .query_pairs()
+        .into_owned()
+        .filter_map(rewrite)
+        .collect::<Vec<(String, String)>>();
+    if query.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(query);
+    }
+}
+
+pub(crate) fn strip_access_token(url: &mut Url) {
+    rewrite_query(url, |(key, value)| {
+        (key != "access_token").then_some((key, value))
+    });
+}
+
+fn set_access_token(url: &mut Url, access_token: &str) {
+    strip_access_token(url);
+    url.query_pairs_mut()
+        .append_pair("access_token", access_token);
+}
+
+/// The auth endpoints reject bad credentials with either a 400 or a 401
+fn is_auth_failure(status: StatusCode) -> bool {
+    status == StatusCode::BAD_REQUEST || status == StatusCode::UNAUTHORIZED
+}
+
+/// Check the status of an API response and decode its JSON body
+pub async fn parse_response<T: DeserializeOwned>(response: Response) -> Result<T> {
+    let status = response.status();
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok())
+            .map(StdDuration::from_secs);
+        return Err(Error::RateLimited { retry_after });
+    }
+
+    let body = response.text().await?;
+    if !status.is_success() {
+        return Err(Error::Http { status, body });
+    }
+
+    // Auth responses hold tokens, which must not end up in logged errors
+    serde_json::from_str(&body).map_err(|source| Error::Decode {
+        source,
+        payload: redact::redact_payload(&body),
+    })
 }
@@ -0,0 +1,135 @@
+use futures::stream::{self, LocalBoxStream, StreamExt, TryStreamExt};
+use reqwest::{Method, Request, RequestBuilder, Url};
+use serde::de::DeserializeOwned;
+use std::collections::HashSet;
+
+use crate::client::{
+    api_root, api_url, parse_response, strip_access_token, AuthenticatedParkrunClient,
+};
+use crate::models::parkrun::{ListPage, ListResponse};
+use crate::{Error, Result};
+
+/// Default for [`ParkrunClient::max_pages`](crate::client::ParkrunClient::max_pages)
+pub const DEFAULT_MAX_PAGES: usize = 1000;
+
+/// Fetch a single page of a list endpoint, `into_items` pulls the items out of the response data
+pub async fn fetch_page<R: DeserializeOwned, T>(
+    parkrun_client: &mut AuthenticatedParkrunClient,
+    request: RequestBuilder,
+    into_items: fn(R) -> Vec<T>,
+) -> Result<ListPage<T>> {
+    send_page(parkrun_client, request.build()?, into_items).await
+}
+
+async fn send_page<R: DeserializeOwned, T>(
+    parkrun_client: &mut AuthenticatedParkrunClient,
+    request: Request,
+    into_items: fn(R) -> Vec<T>,
+) -> Result<ListPage<T>> {
+    let response = parkrun_client.send_with_refresh(request).await?;
+    let response = parse_response::<ListResponse<R>>(response).await?;
+    let next = response
+        .links
+        .into_iter()
+        .find(|link| link.rel == "next")
+        .map(|link| link.href);
+    Ok(ListPage {
+        items: into_items(response.data),
+        next,
+    })
+}
+
+/// Resolve a next page link against the API base URL. The API returns `./`-relative links, but
+/// root-relative and absolute links are accepted as long as they stay on the API's origin.
+/// Root-relative links are taken to be relative to the base URL's path unless they already start
+/// with it, as the API doesn't know about any proxy in front of it
+fn resolve_next_link(base_url: &Url, href: &str) -> Result<Url> {
+    let invalid = || Error::InvalidNextLink {
+        href: href.to_string(),
+    };
+    let base_url = api_root(base_url);
+    let root_relative = href.starts_with('/') && !href.starts_with("//");
+    let mut url = if root_relative && !href.starts_with(base_url.path()) {
+        api_url(&base_url, href)
+    } else {
+        base_url.join(href).map_err(Error::from)
+    }
+    .map_err(|_| invalid())?;
+    if url.origin() != base_url.origin() {
+        return Err(invalid());
+    }
+    // The current token is added when the request is sent
+    strip_access_token(&mut url);
+    Ok(url)
+}
+
+/// Identifies a page regardless of the access token it was requested with
+fn page_key(url: &Url) -> String {
+    let mut url = url.clone();
+    strip_access_token(&mut url);
+    url.to_string()
+}
+
+struct Pagination<'a> {
+    parkrun_client: &'a mut AuthenticatedParkrunClient,
+    next: Option<Result<Request>>,
+    visited: HashSet<String>,
+}
+
+impl Pagination<'_> {
+    /// Check the request doesn't revisit a page or go over the page limit before sending it
+    fn check_request(&mut self, request: &Request) -> Result<()> {
+        let max_pages = self.parkrun_client.client.max_pages;
+        if self.visited.len() >= max_pages {
+            return Err(Error::TooManyPages { max_pages });
+        }
+        let url = page_key(request.url());
+        if !self.visited.insert(url.clone()) {
+            return Err(Error::PaginationLoop { url });
+        }
+        Ok(())
+    }
+
+    fn next_request(&self, href: &str) -> Result<Request> {
+        let url = resolve_next_link(&self.parkrun_client.client.base_url, href)?;
+        Ok(self
+            .parkrun_client
+            .request(Method::GET, url.as_str())?
+            .build()?)
+    }
+}
+
+/// Stream the items of a list endpoint starting from `request`, fetching each page as the previous
+/// one is used up
+pub fn stream_pages<'a, R: DeserializeOwned + 'a, T: 'a>(
+    parkrun_client: &'a mut AuthenticatedParkrunClient,
+    request: Result<RequestBuilder>,
+    into_items: fn(R) -> Vec<T>,
+) -> LocalBoxStream<'a, Result<T>> {
+    let pagination = Pagination {
+        parkrun_client,
+        next: Some(request.and_then(|request| Ok(request.build()?))),
+        visited: HashSet::new(),
+    };
+
+    stream::unfold(pagination, move |mut pagination| async move {
+        let page = match pagination.next.take()? {
+            Ok(request) => match pagination.check_request(&request) {
+                Ok(()) => send_page(pagination.parkrun_client, request, into_items).await,
+                Err(err) => Err(err),
+            },
+            Err(err) => Err(err),
+        };
+        // Stop after the first error rather than retrying the same page forever
+        let page = match page {
+            Ok(page) => page,
+            Err(err) => return Some((Err(err), pagination)),
+        };
+
+        pagination.next = page.next.as_ref().map(|href| pagination.next_request(href));
+        Some((Ok(page.items), pagination))
+    })
+    .map_ok(|items| stream::iter(items.into_iter().map(Ok)))
+    .try_flatten()
+    .boxed_local()
+}
@@ -0,0 +1,57 @@
+//! Redaction of secrets from anything that may be logged or written to disk, such as cassettes
+//! and the payloads of decode errors
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Query, form and JSON fields that hold secrets
+const SECRET_FIELDS: [&str; 3] = ["access_token", "refresh_token", "password"];
+
+pub(crate) const REDACTED: &str = "<redacted>";
+
+pub(crate) fn redact_form(body: &[u8]) -> String {
+    let pairs = url::form_urlencoded::parse(body)
+        .into_owned()
+        .map(|(key, value)| redact_pair(key, value))
+        .collect::<BTreeMap<String, String>>();
+    url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(pairs)
+        .finish()
+}
+
+pub(crate) fn redact_pair(key: String, value: String) -> (String, String) {
+    if SECRET_FIELDS.contains(&key.as_str()) {
+        (key, String::from(REDACTED))
+    } else {
+        (key, value)
+    }
+}
+
+pub(crate) fn redact_json(json: &mut Value) {
+    match json {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                if SECRET_FIELDS.contains(&key.as_str()) {
+                    *value = Value::String(String::from(REDACTED));
+                } else {
+                    redact_json(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+/// Redact a response body. JSON bodies have their secret fields redacted, other bodies are
+/// redacted entirely if they mention a secret field as they can't be redacted field by field
+pub(crate) fn redact_payload(body: &str) -> String {
+    match serde_json::from_str::<Value>(body) {
+        Ok(mut json) => {
+            redact_json(&mut json);
+            json.to_string()
+        }
+        Err(_) if SECRET_FIELDS.iter().any(|field| body.contains(field)) => String::from(REDACTED),
+        Err(_) => body.to_string(),
+    }
+}
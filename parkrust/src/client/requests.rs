@@ -1,83 +1,418 @@
-use chrono::Duration;
-use std::{collections::HashSet, time::Duration as StdDuration};
+use chrono::{Datelike, Duration, NaiveDate};
+use std::collections::{HashMap, HashSet};
 
-use crate::models::parkrun::RunResult;
+use crate::models::parkrun::{
+    Athlete, AthleteResults, Finisher, Role, RunResult, RunTime, Sex, VolunteerRole,
+};
 
-pub fn total_time(results: &[RunResult]) -> Duration {
-    let seconds: i64 = results
+/// Stats for a set of results, computed once so they can be shared between views. Stats which are
+/// undefined for an athlete with no results are `None`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub total_runs: usize,
+    pub total_time: RunTime,
+    pub total_distance_km: f32,
+    pub average_time: Option<RunTime>,
+    pub fastest_time: Option<RunTime>,
+    /// Average duration per km
+    pub average_speed: Option<Duration>,
+    pub best_finishing_position: Option<u32>,
+    pub worst_finishing_position: Option<u32>,
+    pub average_finishing_position: Option<f32>,
+    pub median_finishing_position: Option<f32>,
+    pub best_gender_position: Option<u32>,
+    pub events: HashSet<String>,
+}
+
+impl Stats {
+    pub fn from_results(results: &[RunResult]) -> Self {
+        Stats {
+            total_runs: results.len(),
+            total_time: total_time(results),
+            total_distance_km: total_distance_km(results),
+            average_time: average_time(results),
+            fastest_time: fastest_time(results),
+            average_speed: average_speed(results),
+            best_finishing_position: best_finishing_position(results),
+            worst_finishing_position: worst_finishing_position(results),
+            average_finishing_position: average_finishing_position(results),
+            median_finishing_position: median_finishing_position(results),
+            best_gender_position: best_gender_position(results),
+            events: events(results),
+        }
+    }
+
+    /// Number of distinct events run at
+    pub fn event_count(&self) -> usize {
+        self.events.len()
+    }
+}
+
+pub fn total_time(results: &[RunResult]) -> RunTime {
+    results.iter().map(|result| result.run_time).sum()
+}
+
+pub fn average_time(results: &[RunResult]) -> Option<RunTime> {
+    let count: u32 = results.len().try_into().ok().filter(|count| *count > 0)?;
+    Some(total_time(results) / count)
+}
+
+pub fn fastest_time(results: &[RunResult]) -> Option<RunTime> {
+    results.iter().map(|result| result.run_time).min()
+}
+
+pub fn best_finishing_position(results: &[RunResult]) -> Option<u32> {
+    results.iter().map(|result| result.finish_position).min()
+}
+
+pub fn worst_finishing_position(results: &[RunResult]) -> Option<u32> {
+    results.iter().map(|result| result.finish_position).max()
+}
+
+pub fn average_finishing_position(results: &[RunResult]) -> Option<f32> {
+    if results.is_empty() {
+        return None;
+    }
+    let total: u64 = results
         .iter()
-        .map(|result| result.duration().num_seconds())
+        .map(|result| u64::from(result.finish_position))
         .sum();
-    Duration::from_std(StdDuration::from_secs(seconds.try_into().unwrap())).unwrap()
+    Some(total as f32 / results.len() as f32)
+}
+
+pub fn median_finishing_position(results: &[RunResult]) -> Option<f32> {
+    let mut positions = results
+        .iter()
+        .map(|result| result.finish_position)
+        .collect::<Vec<u32>>();
+    positions.sort_unstable();
+
+    let middle = positions.len() / 2;
+    match positions.len() {
+        0 => None,
+        len if len % 2 == 0 => Some((positions[middle - 1] + positions[middle]) as f32 / 2.0),
+        _ => Some(positions[middle] as f32),
+    }
+}
+
+pub fn best_gender_position(results: &[RunResult]) -> Option<u32> {
+    results.iter().map(|result| result.gender_position).min()
 }
 
-pub fn average_time(results: &[RunResult]) -> Duration {
-    total_time(results) / results.len().try_into().unwrap()
+/// Return the average percentile of the field across the results where the field size is known.
+/// `field_size` should return the number of finishers for the run of a result, see
+/// [`RunResult::field_percentile`]
+pub fn average_field_percentile(
+    results: &[RunResult],
+    field_size: impl Fn(&RunResult) -> Option<u32>,
+) -> Option<f32> {
+    let percentiles = results
+        .iter()
+        .filter_map(|result| result.field_percentile(field_size(result)?))
+        .collect::<Vec<f32>>();
+    if percentiles.is_empty() {
+        return None;
+    }
+    Some(percentiles.iter().sum::<f32>() / percentiles.len() as f32)
+}
+
+/// Return average speed. The result is the duration per km, over the total distance run so 5k and
+/// junior 2k results can be mixed
+pub fn average_speed(results: &[RunResult]) -> Option<Duration> {
+    let total_metres: i64 = results
+        .iter()
+        .map(|result| i64::from(result.distance_metres()))
+        .sum();
+    if total_metres == 0 {
+        return None;
+    }
+    let total_milliseconds: i64 = results
+        .iter()
+        .map(|result| result.duration().num_milliseconds())
+        .sum();
+    Some(Duration::seconds(total_milliseconds / total_metres))
 }
 
-pub fn fastest_time(results: &[RunResult]) -> Duration {
+/// Total distance run in km
+pub fn total_distance_km(results: &[RunResult]) -> f32 {
     results
         .iter()
-        .map(|result| result.duration())
-        .min()
-        .unwrap()
+        .map(|result| result.distance_metres())
+        .sum::<u32>() as f32
+        / 1000.0
+}
+
+pub fn events(results: &[RunResult]) -> HashSet<String> {
+    HashSet::from_iter(results.iter().map(|result| result.event_number.clone()))
 }
 
-pub fn best_finishing_position(results: &[RunResult]) -> Duration {
+/// Results sorted oldest first, as the API returns them newest first. Results on the same day keep
+/// their order
+pub(crate) fn chronological(results: &[RunResult]) -> Vec<&RunResult> {
+    let mut results = results.iter().collect::<Vec<&RunResult>>();
+    results.sort_by_key(|result| result.event_date);
     results
+}
+
+/// Where a result placed against everyone who finished the same run
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldPlacing {
+    pub position: u32,
+    pub field_size: u32,
+    /// `None` when the athlete's sex isn't known
+    pub gender_position: Option<u32>,
+    pub gender_field_size: u32,
+    /// `None` when the athlete's category isn't known
+    pub category_position: Option<u32>,
+    pub category_field_size: u32,
+    /// The result that finished immediately ahead, `None` for the winner
+    pub ahead: Option<Finisher>,
+    /// The result that finished immediately behind, `None` for the last finisher
+    pub behind: Option<Finisher>,
+}
+
+impl FieldPlacing {
+    /// See [`RunResult::field_percentile`]
+    pub fn percentile(&self) -> Option<f32> {
+        field_percentile(self.position, self.field_size)
+    }
+}
+
+/// The percentage of a field of `field_size` that finished behind `position`, `None` if the
+/// position is not within the field
+pub fn field_percentile(position: u32, field_size: u32) -> Option<f32> {
+    if position == 0 || position > field_size {
+        return None;
+    }
+    Some((field_size - position) as f32 / field_size as f32 * 100.0)
+}
+
+/// Place `result` against `finishers`, every finisher of the run it is from as fetched with
+/// [`RunResult::finishers`]. `None` if the athlete isn't among the finishers
+pub fn field_placing(result: &RunResult, finishers: &[Finisher]) -> Option<FieldPlacing> {
+    let mut finishers = finishers.iter().collect::<Vec<&Finisher>>();
+    finishers.sort_by_key(|finisher| finisher.finish_position);
+    let index = finishers
         .iter()
-        .map(|result| result.duration())
-        .min()
-        .unwrap()
+        .position(|finisher| finisher.athlete_id == result.athlete_id)?;
+    let athlete = finishers[index];
+
+    // Positions within a group are counted rather than taken from the API, which only sends gender
+    // positions
+    let place_within = |same_group: &dyn Fn(&Finisher) -> bool| {
+        let group = finishers
+            .iter()
+            .filter(|finisher| same_group(finisher))
+            .collect::<Vec<_>>();
+        let position = group
+            .iter()
+            .position(|finisher| finisher.athlete_id == athlete.athlete_id)
+            .map(|index| index as u32 + 1);
+        (position, group.len() as u32)
+    };
+    let (gender_position, gender_field_size) = match athlete.sex {
+        Some(sex) => place_within(&|finisher| finisher.sex == Some(sex)),
+        None => (None, 0),
+    };
+    let (category_position, category_field_size) = match athlete.age_category {
+        Some(category) => place_within(&|finisher| finisher.age_category == Some(category)),
+        None => (None, 0),
+    };
+
+    Some(FieldPlacing {
+        position: athlete.finish_position,
+        field_size: finishers.len() as u32,
+        gender_position,
+        gender_field_size,
+        category_position,
+        category_field_size,
+        ahead: index.checked_sub(1).map(|index| finishers[index].clone()),
+        behind: finishers.get(index + 1).map(|finisher| (*finisher).clone()),
+    })
+}
+
+/// The fastest finishers of each sex
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CourseRecords {
+    pub male: Option<Finisher>,
+    pub female: Option<Finisher>,
+}
+
+impl CourseRecords {
+    pub fn for_sex(&self, sex: Sex) -> Option<&Finisher> {
+        match sex {
+            Sex::Male => self.male.as_ref(),
+            Sex::Female => self.female.as_ref(),
+        }
+    }
 }
 
-pub fn average_finishing_position(results: &[RunResult]) -> Duration {
+/// The course records over `finishers`, such as the finishers of every run at an event fetched
+/// with [`Event::run_finishers`]. Unknown finishers and finishers of unknown sex are left out, and
+/// ties go to the earliest run
+///
+/// [`Event::run_finishers`]: crate::models::parkrun::Event::run_finishers
+pub fn course_records(finishers: &[Finisher]) -> CourseRecords {
+    let record = |sex: Sex| {
+        finishers
+            .iter()
+            .filter(|finisher| !finisher.is_unknown() && finisher.sex == Some(sex))
+            .min_by_key(|finisher| {
+                (
+                    finisher.run_time,
+                    finisher.run_id.parse::<u32>().unwrap_or(u32::MAX),
+                    finisher.finish_position,
+                )
+            })
+            .cloned()
+    };
+    CourseRecords {
+        male: record(Sex::Male),
+        female: record(Sex::Female),
+    }
+}
+
+/// Stats for a club, built from every member's results with [`Club::member_results`]
+///
+/// [`Club::member_results`]: crate::models::parkrun::Club::member_results
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClubStats {
+    pub total_members: usize,
+    /// Stats over every result members ran in the season
+    pub season: Stats,
+    /// Genuine PBs members ran in the current month, newest first
+    pub pbs_this_month: Vec<MemberPb>,
+}
+
+/// A PB run by a club member
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemberPb {
+    pub athlete: Athlete,
+    pub result: RunResult,
+}
+
+impl ClubStats {
+    /// Compute stats for the season running from `season_start` to `today`, both inclusive.
+    /// The current month is the month of `today`
+    pub fn from_members(
+        members: &[AthleteResults],
+        season_start: NaiveDate,
+        today: NaiveDate,
+    ) -> Self {
+        let season_results = members
+            .iter()
+            .flat_map(|member| results_between(&member.results, season_start, today))
+            .cloned()
+            .collect::<Vec<RunResult>>();
+
+        let mut pbs_this_month = members
+            .iter()
+            .flat_map(|member| {
+                pbs_in_month(&member.results, today).map(|result| MemberPb {
+                    athlete: member.athlete.clone(),
+                    result: result.clone(),
+                })
+            })
+            .collect::<Vec<MemberPb>>();
+        pbs_this_month.sort_by_key(|pb| std::cmp::Reverse(pb.result.event_date));
+
+        ClubStats {
+            total_members: members.len(),
+            season: Stats::from_results(&season_results),
+            pbs_this_month,
+        }
+    }
+
+    /// Number of runs by members this season
+    pub fn season_runs(&self) -> usize {
+        self.season.total_runs
+    }
+}
+
+/// Results run from `start` to `end`, both inclusive
+pub fn results_between(
+    results: &[RunResult],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> impl Iterator<Item = &RunResult> {
     results
         .iter()
-        .map(|result| result.duration())
-        .min()
-        .unwrap()
+        .filter(move |result| (start..=end).contains(&result.event_date))
 }
 
-/// Return average speed. The result is the duration per km
-pub fn average_speed(results: &[RunResult]) -> Duration {
-    let num_seconds: i64 = results
+/// Genuine PBs run in the same month as `date`
+pub fn pbs_in_month(results: &[RunResult], date: NaiveDate) -> impl Iterator<Item = &RunResult> {
+    results.iter().filter(move |result| {
+        result.genuine_pb
+            && result.event_date.year() == date.year()
+            && result.event_date.month() == date.month()
+    })
+}
+
+/// Stats for a volunteer history, the volunteering counterpart of [`Stats`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct VolunteerStats {
+    /// Number of roles volunteered, counting each role at a run separately
+    pub total_roles: usize,
+    /// Number of runs volunteered at, which is what parkrun's volunteer milestones count
+    pub total_credits: usize,
+    /// Roles with how often each was volunteered, most frequent first
+    pub roles_by_count: Vec<(Role, usize)>,
+    pub events: HashSet<String>,
+}
+
+impl VolunteerStats {
+    pub fn from_roles(roles: &[VolunteerRole]) -> Self {
+        VolunteerStats {
+            total_roles: roles.len(),
+            total_credits: volunteer_credits(roles),
+            roles_by_count: roles_by_count(roles),
+            events: volunteer_events(roles),
+        }
+    }
+
+    /// The role volunteered most often, ties going to the role first alphabetically
+    pub fn most_common_role(&self) -> Option<&Role> {
+        self.roles_by_count.first().map(|(role, _)| role)
+    }
+
+    /// Number of distinct events volunteered at
+    pub fn event_count(&self) -> usize {
+        self.events.len()
+    }
+}
+
+/// Count volunteer credits. parkrun gives one credit per run however many roles were volunteered
+pub fn volunteer_credits(roles: &[VolunteerRole]) -> usize {
+    roles
         .iter()
-        .map(|result| result.speed().num_seconds())
-        .sum();
-    let average_num_seconds: i64 = num_seconds / results.len() as i64;
-    Duration::from_std(StdDuration::from_secs(average_num_seconds as u64)).unwrap()
+        .map(|role| (&role.event_number, role.event_date))
+        .collect::<HashSet<_>>()
+        .len()
 }
 
-pub fn events(results: &[RunResult]) -> HashSet<String> {
-    HashSet::from_iter(results.iter().map(|result| result.event_number.clone()))
+/// Count how often each role was volunteered, most frequent first and then alphabetically by role
+pub fn roles_by_count(roles: &[VolunteerRole]) -> Vec<(Role, usize)> {
+    let mut counts = HashMap::<&Role, usize>::new();
+    for role in roles {
+        *counts.entry(&role.role).or_default() += 1;
+    }
+    let mut counts = counts
+        .into_iter()
+        .map(|(role, count)| (role.clone(), count))
+        .collect::<Vec<(Role, usize)>>();
+    counts.sort_by(|(role, count), (other_role, other_count)| {
+        other_count
+            .cmp(count)
+            .then_with(|| role.as_str().cmp(other_role.as_str()))
+    });
+    counts
+}
+
+pub fn volunteer_events(roles: &[VolunteerRole]) -> HashSet<String> {
+    HashSet::from_iter(roles.iter().map(|role| role.event_number.clone()))
 }
 
 pub fn duration_formatter(duration: Duration) -> String {
-    let seconds = duration.num_seconds() % 60;
-    let minutes = (duration.num_seconds() / 60) % 60;
-    let hours = (duration.num_seconds() / 60) / 60;
-
-    if hours == 0 {
-        format!("{minutes:0>2}:{seconds:0>2}")
-    } else {
-        format!("{hours:0>2}:{minutes:0>2}:{seconds:0>2}")
-    }
-}
-
-// #[cfg(test)]
-// mod tests {
-//     // Note this useful idiom: importing names from outer (for mod tests) scope.
-//
-//
-//     // macro_rules! duration_test {
-//     //     ($name:ident, $input:expr, $expected:expr) => {
-//     //         #[test]
-//     //         fn $name() {
-//     //             assert_eq!($expected, duration_formatter($input));
-//     //         }
-//     //     };
-//     // }
-//
-//     // duration_test!()
-// }
+    RunTime::from(duration).to_string()
+}
@@ -0,0 +1,149 @@
+use std::sync::{Arc, Mutex};
+
+use crate::client::Token;
+use crate::Result;
+
+/// Somewhere to persist a [`Token`] between sessions. Attach one to an
+/// [`AuthenticatedParkrunClient`](crate::client::AuthenticatedParkrunClient) with
+/// `with_token_store` and refreshed tokens are saved automatically
+pub trait TokenStore: Send + Sync {
+    /// Load the saved token, `None` if no token has been saved
+    fn load(&self) -> Result<Option<Token>>;
+
+    fn save(&self, token: &Token) -> Result<()>;
+
+    fn clear(&self) -> Result<()>;
+}
+
+/// Keeps the token in memory, useful for tests and short lived processes
+#[derive(Default)]
+pub struct MemoryTokenStore {
+    token: Mutex<Option<Token>>,
+}
+
+impl MemoryTokenStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl TokenStore for MemoryTokenStore {
+    fn load(&self) -> Result<Option<Token>> {
+        Ok(self.token.lock().unwrap().clone())
+    }
+
+    fn save(&self, token: &Token) -> Result<()> {
+        *self.token.lock().unwrap() = Some(token.clone());
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<()> {
+        *self.token.lock().unwrap() = None;
+        Ok(())
+    }
+}
+
+/// Share a store between a client and the rest of the app
+impl<T: TokenStore + ?Sized> TokenStore for Arc<T> {
+    fn load(&self) -> Result<Option<Token>> {
+        (**self).load()
+    }
+
+    fn save(&self, token: &Token) -> Result<()> {
+        (**self).save(token)
+    }
+
+    fn clear(&self) -> Result<()> {
+        (**self).clear()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use file::FileTokenStore;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod file {
+    use std::fs::{self, OpenOptions};
+    use std::io::{ErrorKind, Write};
+    use std::path::{Path, PathBuf};
+
+    use super::TokenStore;
+    use crate::client::Token;
+    use crate::{Error, Result};
+
+    /// Saves the token as JSON in a file only readable by the current user
+    pub struct FileTokenStore {
+        path: PathBuf,
+    }
+
+    impl FileTokenStore {
+        pub fn new(path: impl Into<PathBuf>) -> Self {
+            FileTokenStore { path: path.into() }
+        }
+
+        /// Store the token at `$XDG_CONFIG_HOME/parkrust/token.json`, falling back to
+        /// `~/.config/parkrust/token.json`
+        pub fn in_config_dir() -> Result<Self> {
+            let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from)
+                .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+                .ok_or_else(|| {
+                    Error::TokenStore(String::from(
+                        "could not find a config directory, set XDG_CONFIG_HOME or HOME",
+                    ))
+                })?;
+            Ok(FileTokenStore::new(
+                config_dir.join("parkrust").join("token.json"),
+            ))
+        }
+
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+    }
+
+    impl TokenStore for FileTokenStore {
+        fn load(&self) -> Result<Option<Token>> {
+            let contents = match fs::read_to_string(&self.path) {
+                Ok(contents) => contents,
+                Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+                Err(err) => return Err(err.into()),
+            };
+            // Don't include the contents in the error, they contain the tokens
+            serde_json::from_str(&contents).map(Some).map_err(|err| {
+                Error::TokenStore(format!("invalid token file {}: {err}", self.path.display()))
+            })
+        }
+
+        fn save(&self, token: &Token) -> Result<()> {
+            if let Some(parent) = self.path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            let mut options = OpenOptions::new();
+            options.write(true).create(true).truncate(true);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+                options.mode(0o600);
+                // The mode is only applied when creating the file, so tighten existing files too
+                if self.path.exists() {
+                    fs::set_permissions(&self.path, fs::Permissions::from_mode(0o600))?;
+                }
+            }
+
+            let contents = serde_json::to_string(token)
+                .map_err(|err| Error::TokenStore(format!("failed to serialize token: {err}")))?;
+            options.open(&self.path)?.write_all(contents.as_bytes())?;
+            Ok(())
+        }
+
+        fn clear(&self) -> Result<()> {
+            match fs::remove_file(&self.path) {
+                Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+                _ => Ok(()),
+            }
+        }
+    }
+}
@@ -0,0 +1,283 @@
+//! Compare the results of several athletes: head-to-head records at runs they both finished,
+//! events they have all run, how their PBs came down and how their age grades are trending.
+
+use chrono::NaiveDate;
+use std::collections::{HashMap, HashSet};
+
+use crate::client::requests::events;
+use crate::models::parkrun::{AthleteResults, RunResult, RunTime, Series};
+use crate::progression::pb_ladder;
+
+/// A run two athletes both finished
+#[derive(Debug, Clone, PartialEq)]
+pub struct Encounter {
+    pub event_number: String,
+    pub run_id: String,
+    pub event_date: NaiveDate,
+    pub position: u32,
+    pub other_position: u32,
+    pub age_grading: f32,
+    pub other_age_grading: f32,
+}
+
+impl Encounter {
+    pub fn finished_ahead(&self) -> bool {
+        self.position < self.other_position
+    }
+
+    /// Age grade percentage points ahead of the other athlete, negative when behind
+    pub fn age_grade_difference(&self) -> f32 {
+        self.age_grading - self.other_age_grading
+    }
+}
+
+/// The record of one athlete against another at the runs they both finished
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeadToHead {
+    pub athlete_id: String,
+    pub other_athlete_id: String,
+    /// Oldest first
+    pub encounters: Vec<Encounter>,
+}
+
+impl HeadToHead {
+    /// Number of encounters the athlete finished ahead of the other athlete
+    pub fn wins(&self) -> usize {
+        self.encounters
+            .iter()
+            .filter(|encounter| encounter.finished_ahead())
+            .count()
+    }
+
+    /// Number of encounters the other athlete finished ahead
+    pub fn losses(&self) -> usize {
+        self.encounters.len() - self.wins()
+    }
+
+    pub fn average_age_grade_difference(&self) -> Option<f32> {
+        if self.encounters.is_empty() {
+            return None;
+        }
+        let total: f32 = self
+            .encounters
+            .iter()
+            .map(Encounter::age_grade_difference)
+            .sum();
+        Some(total / self.encounters.len() as f32)
+    }
+
+    /// How the age grade difference has changed over the encounters, a positive slope means the
+    /// athlete is pulling away from the other athlete
+    pub fn age_grade_trend(&self) -> Trend {
+        Trend {
+            athlete_id: self.athlete_id.clone(),
+            points: self
+                .encounters
+                .iter()
+                .map(|encounter| TrendPoint {
+                    date: encounter.event_date,
+                    value: encounter.age_grade_difference(),
+                })
+                .collect(),
+        }
+    }
+
+    /// The same record from the other athlete's side
+    pub fn reversed(&self) -> HeadToHead {
+        HeadToHead {
+            athlete_id: self.other_athlete_id.clone(),
+            other_athlete_id: self.athlete_id.clone(),
+            encounters: self
+                .encounters
+                .iter()
+                .map(|encounter| Encounter {
+                    position: encounter.other_position,
+                    other_position: encounter.position,
+                    age_grading: encounter.other_age_grading,
+                    other_age_grading: encounter.age_grading,
+                    ..encounter.clone()
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Results are the same run when both the event and the run id match, run ids are only unique
+/// within an event
+fn run_key(result: &RunResult) -> (&str, &str) {
+    (&result.event_number, &result.run_id)
+}
+
+pub fn head_to_head(athlete: &AthleteResults, other: &AthleteResults) -> HeadToHead {
+    let other_results = other
+        .results
+        .iter()
+        .map(|result| (run_key(result), result))
+        .collect::<HashMap<_, _>>();
+
+    let mut encounters = athlete
+        .results
+        .iter()
+        .filter_map(|result| {
+            let other_result = other_results.get(&run_key(result))?;
+            Some(Encounter {
+                event_number: result.event_number.clone(),
+                run_id: result.run_id.clone(),
+                event_date: result.event_date,
+                position: result.finish_position,
+                other_position: other_result.finish_position,
+                age_grading: result.age_grading,
+                other_age_grading: other_result.age_grading,
+            })
+        })
+        .collect::<Vec<Encounter>>();
+    encounters.sort_by_key(|encounter| encounter.event_date);
+
+    HeadToHead {
+        athlete_id: athlete.athlete_id().to_string(),
+        other_athlete_id: other.athlete_id().to_string(),
+        encounters,
+    }
+}
+
+/// Head-to-head records for every pair of athletes, each pair once in the order given
+pub fn head_to_heads(athletes: &[AthleteResults]) -> Vec<HeadToHead> {
+    athletes
+        .iter()
+        .enumerate()
+        .flat_map(|(index, athlete)| {
+            athletes[index + 1..]
+                .iter()
+                .map(move |other| head_to_head(athlete, other))
+        })
+        .collect()
+}
+
+/// Events every one of the athletes has run at
+pub fn shared_events(athletes: &[AthleteResults]) -> HashSet<String> {
+    let mut athlete_events = athletes.iter().map(|athlete| events(&athlete.results));
+    let Some(first) = athlete_events.next() else {
+        return HashSet::new();
+    };
+    athlete_events.fold(first, |shared, events| {
+        shared.intersection(&events).cloned().collect()
+    })
+}
+
+/// A new PB
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PbPoint {
+    pub date: NaiveDate,
+    pub run_time: RunTime,
+}
+
+/// How an athlete's PB came down over time, for charting athletes' PBs against each other
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PbRace {
+    pub athlete_id: String,
+    /// Each time the PB improved, oldest first. The first point is the athlete's first run
+    pub points: Vec<PbPoint>,
+}
+
+impl PbRace {
+    /// The PB as it stood on `date`, `None` before the athlete's first run
+    pub fn pb_on(&self, date: NaiveDate) -> Option<RunTime> {
+        self.points
+            .iter()
+            .take_while(|point| point.date <= date)
+            .last()
+            .map(|point| point.run_time)
+    }
+}
+
+/// Each time the PB improved over the results in `series`, see [`pb_ladder`]
+pub fn pb_history(results: &[RunResult], series: Series) -> Vec<PbPoint> {
+    pb_ladder(results, series)
+        .into_iter()
+        .map(|pb| PbPoint {
+            date: pb.date,
+            run_time: pb.run_time,
+        })
+        .collect()
+}
+
+/// PB histories for each athlete over results in `series`
+pub fn pb_race(athletes: &[AthleteResults], series: Series) -> Vec<PbRace> {
+    athletes
+        .iter()
+        .map(|athlete| PbRace {
+            athlete_id: athlete.athlete_id().to_string(),
+            points: pb_history(&athlete.results, series),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrendPoint {
+    pub date: NaiveDate,
+    pub value: f32,
+}
+
+/// A value over time, such as an athlete's age grade
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trend {
+    pub athlete_id: String,
+    /// Oldest first
+    pub points: Vec<TrendPoint>,
+}
+
+impl Trend {
+    /// Change in the value per year from a least squares fit, `None` with fewer than two dates
+    pub fn slope_per_year(&self) -> Option<f32> {
+        let first = self.points.first()?.date;
+        let xs = self
+            .points
+            .iter()
+            .map(|point| (point.date - first).num_days() as f64 / 365.25)
+            .collect::<Vec<f64>>();
+        let count = xs.len() as f64;
+        let mean_x = xs.iter().sum::<f64>() / count;
+        let mean_y = self
+            .points
+            .iter()
+            .map(|point| f64::from(point.value))
+            .sum::<f64>()
+            / count;
+
+        let (covariance, variance) =
+            xs.iter()
+                .zip(&self.points)
+                .fold((0.0, 0.0), |(covariance, variance), (x, point)| {
+                    (
+                        covariance + (x - mean_x) * (f64::from(point.value) - mean_y),
+                        variance + (x - mean_x).powi(2),
+                    )
+                });
+        if variance == 0.0 {
+            return None;
+        }
+        Some((covariance / variance) as f32)
+    }
+}
+
+/// Age grade trend for each athlete, to compare how each is improving regardless of age and sex
+pub fn age_grade_trends(athletes: &[AthleteResults]) -> Vec<Trend> {
+    athletes
+        .iter()
+        .map(|athlete| {
+            let mut points = athlete
+                .results
+                .iter()
+                .map(|result| TrendPoint {
+                    date: result.event_date,
+                    value: result.age_grading,
+                })
+                .collect::<Vec<TrendPoint>>();
+            points.sort_by_key(|point| point.date);
+            Trend {
+                athlete_id: athlete.athlete_id().to_string(),
+                points,
+            }
+        })
+        .collect()
+}
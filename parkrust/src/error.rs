@@ -0,0 +1,71 @@
+use reqwest::StatusCode;
+use std::time::Duration;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Errors returned by the parkrun client
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The athlete id or password was rejected when authenticating
+    #[error("invalid athlete id or password")]
+    InvalidCredentials,
+
+    /// The refresh token has expired or been revoked, the athlete must log in again
+    #[error("refresh token has expired or been revoked")]
+    InvalidRefreshToken,
+
+    /// The API responded with `429 Too Many Requests`
+    #[error("rate limited by the parkrun API")]
+    RateLimited { retry_after: Option<Duration> },
+
+    /// The API responded with an unexpected status code
+    #[error("parkrun API responded with {status}: {body}")]
+    Http { status: StatusCode, body: String },
+
+    /// The API responded successfully but did not include the requested resource
+    #[error("not found: {0}")]
+    NotFound(String),
+
+    /// A next page link could not be resolved, or pointed away from the API
+    #[error("invalid next page link {href:?}")]
+    InvalidNextLink { href: String },
+
+    /// A next page link pointed back to a page that had already been fetched
+    #[error("pagination loop at {url}")]
+    PaginationLoop { url: String },
+
+    /// Listing would fetch more than the client's `max_pages`
+    #[error("stopped listing after {max_pages} pages")]
+    TooManyPages { max_pages: usize },
+
+    /// The response body did not match the expected schema. Tokens and passwords in the payload are
+    /// redacted
+    #[error("failed to decode parkrun API response: {source}")]
+    Decode {
+        source: serde_json::Error,
+        payload: String,
+    },
+
+    /// A [`TokenStore`](crate::client::TokenStore) failed to load or save a token
+    #[error("token store error: {0}")]
+    TokenStore(String),
+
+    /// A cassette could not be read or written, or had no response recorded for a request
+    #[error("cassette error: {0}")]
+    Cassette(String),
+
+    /// A [`ParkrunClientBuilder`](crate::client::ParkrunClientBuilder) was given options that
+    /// can't be used together
+    #[error("invalid client configuration: {0}")]
+    InvalidConfig(String),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("invalid url: {0}")]
+    InvalidUrl(#[from] url::ParseError),
+
+    /// The request could not be sent or the response could not be read
+    #[error(transparent)]
+    Network(#[from] reqwest::Error),
+}
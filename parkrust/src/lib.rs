@@ -1,2 +1,11 @@
+pub mod age_grade;
+pub mod challenges;
 pub mod client;
+pub mod comparison;
+mod error;
 pub mod models;
+pub mod progression;
+#[cfg(feature = "testing")]
+pub mod testing;
+
+pub use error::{Error, Result};
@@ -0,0 +1,155 @@
+//! Serde adapters for the parkrun API's string encoded values.
+//!
+//! The API sends almost every value as a string (`"1"`, `"0"`, `"2018-03-10"`), these adapters
+//! are used with `#[serde(with = "...")]` to decode them into real types and to encode them back
+//! into the same format.
+
+use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+/// A raw API value which may either be sent as a string or as a JSON primitive
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Raw<T> {
+    Value(T),
+    String(String),
+}
+
+/// Numbers sent as strings, e.g. `"42"` or `"56.78"`
+pub mod number {
+    use super::*;
+    use std::{fmt::Display, str::FromStr};
+
+    pub fn serialize<S: Serializer, T: Display>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: FromStr + Deserialize<'de>,
+        T::Err: Display,
+    {
+        match Raw::<T>::deserialize(deserializer)? {
+            Raw::Value(value) => Ok(value),
+            Raw::String(value) => value
+                .trim()
+                .parse()
+                .map_err(|err| D::Error::custom(format!("invalid number {value:?}: {err}"))),
+        }
+    }
+}
+
+/// Optional values sent as strings, where a missing, `null` or empty value is `None`. Works for any
+/// type which can be parsed from a string e.g. numbers, [`Sex`](crate::models::parkrun::Sex) or
+/// [`AgeCategory`](crate::models::parkrun::AgeCategory). Use together with `#[serde(default)]`
+pub mod optional {
+    use super::*;
+    use std::{fmt::Display, str::FromStr};
+
+    pub fn serialize<S: Serializer, T: Display>(
+        value: &Option<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => serializer.collect_str(value),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: FromStr + Deserialize<'de>,
+        T::Err: Display,
+    {
+        match Option::<Raw<T>>::deserialize(deserializer)? {
+            None => Ok(None),
+            Some(Raw::Value(value)) => Ok(Some(value)),
+            Some(Raw::String(value)) if value.trim().is_empty() => Ok(None),
+            Some(Raw::String(value)) => value
+                .trim()
+                .parse()
+                .map(Some)
+                .map_err(|err| D::Error::custom(format!("invalid value {value:?}: {err}"))),
+        }
+    }
+}
+
+/// Boolean flags sent as `"1"` or `"0"`, where a `null` or empty flag is `false`. Use together with
+/// `#[serde(default)]` so a missing flag is `false` too
+pub mod flag {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &bool, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(if *value { "1" } else { "0" })
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum RawFlag {
+            Bool(bool),
+            Number(u8),
+            String(String),
+        }
+
+        match Option::<RawFlag>::deserialize(deserializer)? {
+            None => Ok(false),
+            Some(RawFlag::Bool(value)) => Ok(value),
+            Some(RawFlag::Number(0)) => Ok(false),
+            Some(RawFlag::Number(1)) => Ok(true),
+            Some(RawFlag::Number(value)) => Err(D::Error::custom(format!("invalid flag {value}"))),
+            Some(RawFlag::String(value)) => match value.trim() {
+                "" | "0" | "false" => Ok(false),
+                "1" | "true" => Ok(true),
+                _ => Err(D::Error::custom(format!("invalid flag {value:?}"))),
+            },
+        }
+    }
+}
+
+/// Dates sent as `2018-03-10`
+pub mod date {
+    use super::*;
+    use chrono::NaiveDate;
+
+    const FORMAT: &str = "%Y-%m-%d";
+
+    pub fn serialize<S: Serializer>(value: &NaiveDate, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&value.format(FORMAT))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveDate, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        NaiveDate::parse_from_str(value.trim(), FORMAT)
+            .map_err(|err| D::Error::custom(format!("invalid date {value:?}: {err}")))
+    }
+}
+
+/// Date times sent as `2018-03-10 12:34:56`
+pub mod date_time {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    const FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+    const ISO_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+    pub fn serialize<S: Serializer>(
+        value: &NaiveDateTime,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&value.format(FORMAT))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<NaiveDateTime, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        NaiveDateTime::parse_from_str(value.trim(), FORMAT)
+            .or_else(|_| NaiveDateTime::parse_from_str(value.trim(), ISO_FORMAT))
+            .map_err(|err| D::Error::custom(format!("invalid date time {value:?}: {err}")))
+    }
+}
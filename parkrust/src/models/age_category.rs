@@ -0,0 +1,218 @@
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Sex {
+    Male,
+    Female,
+}
+
+/// The broad class of a category, parkrun codes these as the first letter e.g. `V` in `VW50-54`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AgeClass {
+    Junior,
+    Senior,
+    Veteran,
+    /// Wheelchair categories are not split by age
+    Wheelchair,
+}
+
+/// The youngest age an athlete can take part in junior parkrun, the lower bound of categories
+/// such as `JM10`
+pub const YOUNGEST_JUNIOR_AGE: u8 = 4;
+
+/// A parkrun age category such as `JM10`, `JM15-17`, `SW25-29`, `VM100+` or `MWC`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AgeCategory {
+    pub sex: Sex,
+    pub class: AgeClass,
+    /// Youngest age in the category, `None` for the youngest juniors e.g. `JM10` and wheelchair
+    /// categories
+    pub min_age: Option<u8>,
+    /// Oldest age in the category, `None` when the category is open ended or a wheelchair category
+    pub max_age: Option<u8>,
+    /// Whether an open ended category is written with a trailing `+` e.g. `VM100+` rather than
+    /// `VM100`, the API uses both
+    pub open_ended_plus: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid sex {0:?}")]
+pub struct ParseSexError(String);
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid age category {0:?}")]
+pub struct ParseAgeCategoryError(String);
+
+impl Sex {
+    /// The letter parkrun uses for this sex in category codes
+    pub fn code(&self) -> char {
+        match self {
+            Sex::Male => 'M',
+            Sex::Female => 'W',
+        }
+    }
+}
+
+impl FromStr for Sex {
+    type Err = ParseSexError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_uppercase().as_str() {
+            "M" | "MALE" => Ok(Sex::Male),
+            "W" | "F" | "FEMALE" => Ok(Sex::Female),
+            _ => Err(ParseSexError(value.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Sex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl AgeCategory {
+    pub fn is_junior(&self) -> bool {
+        self.class == AgeClass::Junior
+    }
+
+    pub fn is_senior(&self) -> bool {
+        self.class == AgeClass::Senior
+    }
+
+    pub fn is_veteran(&self) -> bool {
+        self.class == AgeClass::Veteran
+    }
+
+    pub fn is_wheelchair(&self) -> bool {
+        self.class == AgeClass::Wheelchair
+    }
+
+    /// Whether an athlete of the given age belongs in this category. Wheelchair categories are not
+    /// split by age so never contain an age
+    pub fn contains_age(&self, age: u8) -> bool {
+        match (self.min_age, self.max_age) {
+            (Some(min_age), Some(max_age)) => (min_age..=max_age).contains(&age),
+            (Some(min_age), None) => age >= min_age,
+            (None, Some(max_age)) => age <= max_age,
+            (None, None) => false,
+        }
+    }
+
+    /// The parkrun code for this category e.g. `VW50-54`
+    pub fn code(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl FromStr for AgeCategory {
+    type Err = ParseAgeCategoryError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let error = || ParseAgeCategoryError(value.to_string());
+        let code = value.trim().to_ascii_uppercase();
+
+        if let Some(sex) = code.strip_suffix("WC") {
+            return Ok(AgeCategory {
+                sex: sex.parse().map_err(|_| error())?,
+                class: AgeClass::Wheelchair,
+                min_age: None,
+                max_age: None,
+                open_ended_plus: false,
+            });
+        }
+
+        let mut chars = code.chars();
+        let class = match chars.next() {
+            Some('J') => AgeClass::Junior,
+            Some('S') => AgeClass::Senior,
+            Some('V') => AgeClass::Veteran,
+            _ => return Err(error()),
+        };
+        let sex = match chars.next() {
+            Some('M') => Sex::Male,
+            Some('W') => Sex::Female,
+            _ => return Err(error()),
+        };
+
+        let ages = chars.as_str();
+        let parse_age = |age: &str| age.parse::<u8>().map_err(|_| error());
+        let open_ended_plus = ages.ends_with('+');
+        let (min_age, max_age) = if let Some(min_age) = ages.strip_suffix('+') {
+            (Some(parse_age(min_age)?), None)
+        } else if let Some((min_age, max_age)) = ages.split_once('-') {
+            (Some(parse_age(min_age)?), Some(parse_age(max_age)?))
+        } else {
+            // Single age junior categories such as JM10 are the youngest juniors, up to and
+            // including that age, while VM100 is open ended
+            let age = parse_age(ages)?;
+            match class {
+                AgeClass::Junior => (None, Some(age)),
+                _ => (Some(age), None),
+            }
+        };
+
+        if let (Some(min_age), Some(max_age)) = (min_age, max_age) {
+            if max_age < min_age {
+                return Err(error());
+            }
+        }
+
+        Ok(AgeCategory {
+            sex,
+            class,
+            min_age,
+            max_age,
+            open_ended_plus,
+        })
+    }
+}
+
+impl fmt::Display for AgeCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let class = match self.class {
+            AgeClass::Junior => 'J',
+            AgeClass::Senior => 'S',
+            AgeClass::Veteran => 'V',
+            AgeClass::Wheelchair => return write!(f, "{}WC", self.sex),
+        };
+        write!(f, "{class}{}", self.sex)?;
+        match (self.min_age, self.max_age) {
+            (Some(min_age), Some(max_age)) if min_age == max_age => write!(f, "{min_age}"),
+            (Some(min_age), Some(max_age)) => write!(f, "{min_age}-{max_age}"),
+            (None, Some(max_age)) => write!(f, "{max_age}"),
+            (Some(min_age), None) if self.open_ended_plus => write!(f, "{min_age}+"),
+            (Some(min_age), None) => write!(f, "{min_age}"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl Serialize for Sex {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Sex {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for AgeCategory {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for AgeCategory {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,220 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{convert::Infallible, fmt, str::FromStr};
+
+use crate::models::adapters;
+
+/// Whether an event is currently being held
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EventStatus {
+    Live,
+    /// Temporarily not taking place, e.g. while the course is unavailable
+    Suspended,
+    /// Permanently stopped
+    Closed,
+    /// A status this crate doesn't know about yet
+    Other(String),
+}
+
+impl EventStatus {
+    pub fn as_str(&self) -> &str {
+        match self {
+            EventStatus::Live => "Live",
+            EventStatus::Suspended => "Suspended",
+            EventStatus::Closed => "Closed",
+            EventStatus::Other(status) => status,
+        }
+    }
+}
+
+impl FromStr for EventStatus {
+    type Err = Infallible;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Ok(match value.trim().to_ascii_lowercase().as_str() {
+            "live" => EventStatus::Live,
+            "suspended" => EventStatus::Suspended,
+            "closed" => EventStatus::Closed,
+            _ => EventStatus::Other(value.to_string()),
+        })
+    }
+}
+
+impl fmt::Display for EventStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for EventStatus {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for EventStatus {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Ok(value.parse().unwrap_or_else(|never| match never {}))
+    }
+}
+
+/// The kind of event a result or event belongs to, from the API's `SeriesID`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Series {
+    /// The standard 5 km parkrun
+    FiveK,
+    /// junior parkrun, 2 km for 4 to 14 year olds
+    Junior,
+    /// A series this crate doesn't know about yet
+    Other(u16),
+}
+
+impl Series {
+    pub fn id(&self) -> u16 {
+        match self {
+            Series::FiveK => 1,
+            Series::Junior => 2,
+            Series::Other(id) => *id,
+        }
+    }
+
+    /// The course distance in metres. parkrun only holds 5k and 2k events, so unknown series are
+    /// assumed to be 5k
+    pub fn distance_metres(&self) -> u32 {
+        match self {
+            Series::Junior => 2000,
+            Series::FiveK | Series::Other(_) => 5000,
+        }
+    }
+
+    pub fn distance_km(&self) -> f32 {
+        self.distance_metres() as f32 / 1000.0
+    }
+}
+
+impl From<u16> for Series {
+    fn from(id: u16) -> Self {
+        match id {
+            1 => Series::FiveK,
+            2 => Series::Junior,
+            id => Series::Other(id),
+        }
+    }
+}
+
+impl fmt::Display for Series {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Series::FiveK => f.write_str("parkrun"),
+            Series::Junior => f.write_str("junior parkrun"),
+            Series::Other(id) => write!(f, "Series {id}"),
+        }
+    }
+}
+
+impl Serialize for Series {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        adapters::number::serialize(&self.id(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Series {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        adapters::number::deserialize::<D, u16>(deserializer).map(Series::from)
+    }
+}
+
+/// The country an event is held in, identified by parkrun's numeric country code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Country(pub u16);
+
+/// parkrun's country codes. Codes missing from this list are still decoded, they just have no name
+const COUNTRIES: [(u16, &str); 23] = [
+    (2, "Eswatini"),
+    (3, "Australia"),
+    (4, "Austria"),
+    (14, "Canada"),
+    (23, "Denmark"),
+    (30, "Finland"),
+    (31, "France"),
+    (32, "Germany"),
+    (42, "Ireland"),
+    (44, "Italy"),
+    (46, "Japan"),
+    (54, "Lithuania"),
+    (57, "Malaysia"),
+    (59, "Namibia"),
+    (64, "Netherlands"),
+    (65, "New Zealand"),
+    (67, "Norway"),
+    (74, "Poland"),
+    (82, "Singapore"),
+    (85, "South Africa"),
+    (88, "Sweden"),
+    (97, "United Kingdom"),
+    (98, "United States"),
+];
+
+impl Country {
+    pub fn code(&self) -> u16 {
+        self.0
+    }
+
+    /// The English name of the country, `None` for codes this crate doesn't know
+    pub fn name(&self) -> Option<&'static str> {
+        COUNTRIES
+            .iter()
+            .find(|(code, _)| *code == self.0)
+            .map(|(_, name)| *name)
+    }
+}
+
+impl fmt::Display for Country {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "Country {}", self.0),
+        }
+    }
+}
+
+impl Serialize for Country {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        adapters::number::serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Country {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        adapters::number::deserialize(deserializer).map(Country)
+    }
+}
+
+/// A point on the earth in decimal degrees
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Coordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Coordinates {
+    const EARTH_RADIUS_KM: f64 = 6371.0;
+
+    pub fn new(latitude: f64, longitude: f64) -> Self {
+        Coordinates {
+            latitude,
+            longitude,
+        }
+    }
+
+    /// Great circle distance in kilometres, using the haversine formula
+    pub fn distance_km(&self, other: &Coordinates) -> f64 {
+        let (latitude, other_latitude) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let latitude_delta = other_latitude - latitude;
+        let longitude_delta = (other.longitude - self.longitude).to_radians();
+
+        let a = (latitude_delta / 2.0).sin().powi(2)
+            + latitude.cos() * other_latitude.cos() * (longitude_delta / 2.0).sin().powi(2);
+        2.0 * Self::EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+}
@@ -1 +1,6 @@
+pub mod adapters;
+pub mod age_category;
+pub mod event;
 pub mod parkrun;
+pub mod run_time;
+pub mod volunteer;
@@ -1,19 +1,46 @@
 use async_trait::async_trait;
-use chrono::{Duration, NaiveDate};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use futures::stream::LocalBoxStream;
 use serde::{Deserialize, Serialize};
-use std::time::Duration as StdDuration;
+use std::ops::RangeInclusive;
 
-use crate::client::AuthenticatedParkrunClient;
+use crate::age_grade;
+use crate::client::{encode_path_segment, requests, AuthenticatedParkrunClient};
+use crate::models::adapters;
 use parkrust_derive::{parkrun_list, parkrun_model, parkrun_request_args};
 
+pub use crate::models::age_category::{AgeCategory, AgeClass, Sex, YOUNGEST_JUNIOR_AGE};
+pub use crate::models::event::{Coordinates, Country, EventStatus, Series};
+pub use crate::models::run_time::RunTime;
+pub use crate::models::volunteer::Role;
+
 #[async_trait(?Send)]
 pub trait Listable<Args: Serialize + Send> {
+    /// Fetch every page and collect the items
     async fn list(
         args: Args,
         parkrun_client: &mut AuthenticatedParkrunClient,
-    ) -> Result<Vec<Self>, Box<dyn std::error::Error + Send + Sync>>
+    ) -> crate::Result<Vec<Self>>
     where
         Self: Sized;
+
+    /// Fetch a single page of `limit` items starting at `offset`
+    async fn list_page(
+        args: Args,
+        offset: u32,
+        limit: u32,
+        parkrun_client: &mut AuthenticatedParkrunClient,
+    ) -> crate::Result<ListPage<Self>>
+    where
+        Self: Sized;
+
+    /// Stream the items, fetching each page only once the previous page has been consumed
+    fn stream<'a>(
+        args: Args,
+        parkrun_client: &'a mut AuthenticatedParkrunClient,
+    ) -> LocalBoxStream<'a, crate::Result<Self>>
+    where
+        Self: Sized + 'a;
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -41,12 +68,113 @@ pub struct ListResponse<T> {
     pub links: Vec<ListResponseLink>,
 }
 
+/// A single page of a list endpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListPage<T> {
+    pub items: Vec<T>,
+    /// Link to the next page, `None` on the last page
+    pub next: Option<String>,
+}
+
+impl<T> ListPage<T> {
+    pub fn has_next(&self) -> bool {
+        self.next.is_some()
+    }
+}
+
+/// Run count milestones which earn a free milestone t-shirt
+pub const RUN_MILESTONES: [u32; 6] = [25, 50, 100, 250, 500, 1000];
+/// Junior athletes also get a milestone after 10 runs
+pub const JUNIOR_RUN_MILESTONE: u32 = 10;
+pub const VOLUNTEER_MILESTONES: [u32; 5] = [25, 50, 100, 250, 500];
+
 #[parkrun_model()]
 pub struct Athlete {
-    #[serde(rename(serialize = "AthleteID", deserialize = "AthleteID"))]
     pub athlete_id: String,
     pub first_name: String,
     pub last_name: String,
+    #[serde(default, with = "adapters::optional")]
+    pub sex: Option<Sex>,
+    #[serde(default, with = "adapters::optional")]
+    pub age_category: Option<AgeCategory>,
+    #[serde(default)]
+    pub home_run_id: Option<String>,
+    #[serde(default)]
+    pub home_run_name: Option<String>,
+    #[serde(default)]
+    pub club_id: Option<String>,
+    #[serde(default)]
+    pub club_name: Option<String>,
+    #[serde(default, with = "adapters::optional")]
+    pub run_count: Option<u32>,
+    #[serde(default, with = "adapters::optional")]
+    pub volunteer_count: Option<u32>,
+}
+
+/// Progress towards the next milestone
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MilestoneProgress {
+    pub milestone: u32,
+    pub remaining: u32,
+}
+
+impl Athlete {
+    pub async fn get(
+        athlete_id: &str,
+        parkrun_client: &mut AuthenticatedParkrunClient,
+    ) -> crate::Result<Athlete> {
+        let path = format!(
+            "/v1/athletes/{}",
+            encode_path_segment(normalize_athlete_id(athlete_id))
+        );
+        parkrun_client.get_athlete(&path).await
+    }
+
+    pub fn full_name(&self) -> String {
+        format!("{} {}", self.first_name, self.last_name)
+    }
+
+    fn run_milestones(&self) -> Vec<u32> {
+        let mut milestones = RUN_MILESTONES.to_vec();
+        if self
+            .age_category
+            .is_some_and(|category| category.is_junior())
+        {
+            milestones.insert(0, JUNIOR_RUN_MILESTONE);
+        }
+        milestones
+    }
+
+    /// The highest run milestone reached, this is the milestone t-shirt the athlete can wear
+    pub fn clothing_milestone(&self) -> Option<u32> {
+        let run_count = self.run_count?;
+        self.run_milestones()
+            .into_iter()
+            .rfind(|milestone| *milestone <= run_count)
+    }
+
+    pub fn next_run_milestone(&self) -> Option<MilestoneProgress> {
+        next_milestone(&self.run_milestones(), self.run_count?)
+    }
+
+    pub fn next_volunteer_milestone(&self) -> Option<MilestoneProgress> {
+        next_milestone(&VOLUNTEER_MILESTONES, self.volunteer_count?)
+    }
+}
+
+fn next_milestone(milestones: &[u32], count: u32) -> Option<MilestoneProgress> {
+    milestones
+        .iter()
+        .find(|milestone| **milestone > count)
+        .map(|milestone| MilestoneProgress {
+            milestone: *milestone,
+            remaining: milestone - count,
+        })
+}
+
+/// Strip the `A` prefix parkrun prints on barcodes from an athlete id
+pub fn normalize_athlete_id(athlete_id: &str) -> &str {
+    athlete_id.strip_prefix(['A', 'a']).unwrap_or(athlete_id)
 }
 
 #[parkrun_model()]
@@ -54,6 +182,116 @@ pub struct ListAthletes {
     pub athletes: Vec<Athlete>,
 }
 
+#[parkrun_model()]
+pub struct Club {
+    pub club_id: String,
+    pub club_name: String,
+    #[serde(default)]
+    pub club_url: Option<String>,
+    #[serde(default, with = "adapters::optional")]
+    pub member_count: Option<u32>,
+}
+
+impl Club {
+    pub async fn get(
+        club_id: &str,
+        parkrun_client: &mut AuthenticatedParkrunClient,
+    ) -> crate::Result<Club> {
+        parkrun_client
+            .get_club(&format!("/v1/clubs/{}", encode_path_segment(club_id)))
+            .await
+    }
+
+    /// Fetch every member of the club with the id `club_id`
+    pub async fn members(
+        club_id: &str,
+        parkrun_client: &mut AuthenticatedParkrunClient,
+    ) -> crate::Result<Vec<Athlete>> {
+        let members = ClubMember::list(ClubMembersQuery::new(club_id), parkrun_client).await?;
+        Ok(members.into_iter().map(|member| member.athlete).collect())
+    }
+
+    /// Fetch every member of the club along with all of their results, for
+    /// [`ClubStats`](crate::client::requests::ClubStats)
+    pub async fn member_results(
+        club_id: &str,
+        parkrun_client: &mut AuthenticatedParkrunClient,
+    ) -> crate::Result<Vec<AthleteResults>> {
+        let mut member_results = Vec::new();
+        for athlete in Club::members(club_id, parkrun_client).await? {
+            member_results.push(AthleteResults::for_athlete(athlete, parkrun_client).await?);
+        }
+        Ok(member_results)
+    }
+}
+
+#[parkrun_model()]
+pub struct ListClubs {
+    pub clubs: Vec<Club>,
+}
+
+/// An athlete listed as a member of a club, see [`Club::members`]
+#[parkrun_model()]
+#[parkrun_list(
+    endpoint = "/v1/clubs/{club_id}/members",
+    args_type = "ClubMembersQuery",
+    data_key = "members"
+)]
+pub struct ClubMember {
+    #[serde(flatten)]
+    pub athlete: Athlete,
+}
+
+#[parkrun_request_args()]
+pub struct ClubMembersQuery {
+    #[serde(skip_serializing)]
+    pub club_id: String,
+}
+
+impl ClubMembersQuery {
+    pub fn new(club_id: &str) -> Self {
+        ClubMembersQuery {
+            club_id: club_id.to_string(),
+        }
+    }
+}
+
+/// An athlete and all of their results
+#[derive(Debug, Clone, PartialEq)]
+pub struct AthleteResults {
+    pub athlete: Athlete,
+    pub results: Vec<RunResult>,
+}
+
+impl AthleteResults {
+    /// Fetch the athlete with the id `athlete_id` and their results
+    pub async fn get(
+        athlete_id: &str,
+        parkrun_client: &mut AuthenticatedParkrunClient,
+    ) -> crate::Result<Self> {
+        let athlete = Athlete::get(athlete_id, parkrun_client).await?;
+        AthleteResults::for_athlete(athlete, parkrun_client).await
+    }
+
+    async fn for_athlete(
+        athlete: Athlete,
+        parkrun_client: &mut AuthenticatedParkrunClient,
+    ) -> crate::Result<Self> {
+        let results = RunResult::list(
+            ResultsQuery {
+                athlete_id: athlete.athlete_id.clone(),
+            },
+            parkrun_client,
+        )
+        .await?;
+        Ok(AthleteResults { athlete, results })
+    }
+
+    pub fn athlete_id(&self) -> &str {
+        &self.athlete.athlete_id
+    }
+}
+
 #[parkrun_request_args()]
 pub struct EventsQuery {
     pub athlete_id: String,
@@ -71,17 +309,63 @@ pub struct Event {
     pub event_short_name: String,
     pub event_long_name: String,
     pub event_location: String,
-    pub country_code: String,
+    #[parkrun(field = "CountryCode")]
+    pub country: Country,
     pub preferred_language: String,
-    #[serde(rename(serialize = "SeriesID", deserialize = "SeriesID"))]
-    pub series_id: String,
-    pub next_anniversary: String,
-    pub home_run_selection: String,
-    pub status_live: String,
-    pub anniversary_saturday_of_month: String,
-    pub event_status: String,
-    pub user_favourite: Option<String>,
-    pub accessible_to_public: String,
+    #[parkrun(field = "SeriesID")]
+    pub series: Series,
+    #[serde(default, with = "adapters::optional")]
+    pub next_anniversary: Option<NaiveDate>,
+    #[serde(default, with = "adapters::flag")]
+    pub home_run_selection: bool,
+    #[serde(default, with = "adapters::flag")]
+    pub status_live: bool,
+    #[serde(with = "adapters::number")]
+    pub anniversary_saturday_of_month: u8,
+    pub event_status: EventStatus,
+    #[serde(default, with = "adapters::flag")]
+    pub user_favourite: bool,
+    #[serde(default, with = "adapters::flag")]
+    pub accessible_to_public: bool,
+    /// Not sent by every endpoint, see [`Event::coordinates`]
+    #[serde(default, with = "adapters::optional")]
+    pub latitude: Option<f64>,
+    #[serde(default, with = "adapters::optional")]
+    pub longitude: Option<f64>,
+}
+
+impl Event {
+    pub fn is_live(&self) -> bool {
+        self.status_live && self.event_status == EventStatus::Live
+    }
+
+    /// Where the event is held, if the API included it
+    pub fn coordinates(&self) -> Option<Coordinates> {
+        Some(Coordinates::new(self.latitude?, self.longitude?))
+    }
+
+    /// Distance in kilometres from `coordinates` to the event, if the event's location is known
+    pub fn distance_km(&self, coordinates: &Coordinates) -> Option<f64> {
+        Some(self.coordinates()?.distance_km(coordinates))
+    }
+
+    /// Fetch every finisher of each of the runs `run_ids` at this event, e.g. to work out
+    /// [`course_records`](crate::client::requests::course_records)
+    pub async fn run_finishers(
+        &self,
+        run_ids: impl IntoIterator<Item = &str>,
+        parkrun_client: &mut AuthenticatedParkrunClient,
+    ) -> crate::Result<Vec<Finisher>> {
+        let mut finishers = Vec::new();
+        for run_id in run_ids {
+            let query = RunResultsQuery {
+                event_number: self.event_number.clone(),
+                run_id: run_id.to_string(),
+            };
+            finishers.extend(Finisher::list(query, parkrun_client).await?);
+        }
+        Ok(finishers)
+    }
 }
 
 #[parkrun_request_args()]
@@ -96,50 +380,178 @@ pub struct ResultsQuery {
     data_key = "results"
 )]
 pub struct RunResult {
-    #[serde(rename(serialize = "SeriesID", deserialize = "SeriesID"))]
-    pub series_id: String, // Int
-    pub event_number: String,    // Int
-    pub run_id: String,          // Int
-    pub finish_position: String, // Int
-    pub gender_position: String, // Int,
-    pub event_date: String,      // Date  (2018-03-10)
-    #[serde(rename(serialize = "AthleteID", deserialize = "AthleteID"))]
+    #[parkrun(field = "SeriesID")]
+    pub series: Series,
+    pub event_number: String, // Int
+    #[parkrun(field = "RunId")]
+    pub run_id: String, // Int
+    #[serde(with = "adapters::number")]
+    pub finish_position: u32,
+    #[serde(with = "adapters::number")]
+    pub gender_position: u32,
+    #[serde(with = "adapters::date")]
+    pub event_date: NaiveDate,
     pub athlete_id: String, // Int
-    pub run_time: String,        // Duration
-    pub was_pb_run: String,      // Boolean
-    pub age_grading: String,     // Float
-    pub age_category: String,    // Enum (JM15-17)
-    pub first_timer: String,     // Boolean
-    #[serde(rename(serialize = "GenuinePB", deserialize = "GenuinePB"))]
-    pub genuine_pb: String, // Boolean
-    pub updated: String,         // Date time
-    pub assisted: Option<String>, // Not sure? "0" or probably "1"
+    pub run_time: RunTime,
+    #[serde(default, with = "adapters::flag")]
+    pub was_pb_run: bool,
+    #[serde(with = "adapters::number")]
+    pub age_grading: f32,
+    pub age_category: AgeCategory,
+    #[serde(default, with = "adapters::flag")]
+    pub first_timer: bool,
+    #[serde(default, with = "adapters::flag")]
+    pub genuine_pb: bool,
+    #[serde(with = "adapters::date_time")]
+    pub updated: NaiveDateTime,
+    #[serde(default, with = "adapters::flag")]
+    pub assisted: bool,
 }
 
 impl RunResult {
     pub fn duration(&self) -> Duration {
-        let duration_splits = self.run_time.split(':').collect::<Vec<&str>>();
-        let mins: u32 = String::from(*duration_splits.get(1).unwrap())
-            .parse()
-            .unwrap();
-        let seconds: u32 = String::from(*duration_splits.get(2).unwrap())
-            .parse()
-            .unwrap();
-
-        let duration = StdDuration::new((seconds + mins * 60).into(), 0);
-        Duration::from_std(duration).unwrap()
+        self.run_time.duration()
     }
 
     pub fn date(&self) -> NaiveDate {
-        NaiveDate::parse_from_str(self.event_date.as_str(), "%Y-%m-%d").unwrap()
+        self.event_date
+    }
+
+    pub fn position(&self) -> u32 {
+        self.finish_position
     }
 
-    pub fn position(&self) -> usize {
-        self.finish_position.parse().unwrap()
+    /// Return the percentage of the field that finished behind this result, e.g. finishing 10th of
+    /// 200 is the 95th percentile. Returns `None` if the position is not within the field
+    pub fn field_percentile(&self, field_size: u32) -> Option<f32> {
+        requests::field_percentile(self.finish_position, field_size)
+    }
+
+    /// Course distance in metres, 2k for junior parkrun and 5k otherwise
+    pub fn distance_metres(&self) -> u32 {
+        self.series.distance_metres()
     }
 
     /// Return speed. The result is the duration per km
     pub fn speed(&self) -> Duration {
-        self.duration() / 5
+        Duration::milliseconds(
+            self.duration().num_milliseconds() * 1000 / i64::from(self.distance_metres()),
+        )
+    }
+
+    /// The range of age grades this time could have across the ages in the result's category, to
+    /// check [`RunResult::age_grading`] against. See [`crate::age_grade`]
+    pub fn expected_age_grading(&self) -> Option<RangeInclusive<f32>> {
+        age_grade::age_grade_range(self.age_category, self.distance_metres(), self.run_time)
+    }
+
+    /// Fetch every finisher of the run this result is from
+    pub async fn finishers(
+        &self,
+        parkrun_client: &mut AuthenticatedParkrunClient,
+    ) -> crate::Result<Vec<Finisher>> {
+        Finisher::list(RunResultsQuery::from_result(self), parkrun_client).await
+    }
+}
+
+#[parkrun_request_args()]
+pub struct RunResultsQuery {
+    #[serde(skip_serializing)]
+    pub event_number: String,
+    #[serde(skip_serializing)]
+    pub run_id: String,
+}
+
+impl RunResultsQuery {
+    /// Query the results of the run `result` is from
+    pub fn from_result(result: &RunResult) -> Self {
+        RunResultsQuery {
+            event_number: result.event_number.clone(),
+            run_id: result.run_id.clone(),
+        }
+    }
+}
+
+/// Someone who finished a run, from the full results of that run. Unknown finishers who ran without
+/// a barcode have an athlete id of `0` and no category
+#[parkrun_model()]
+#[parkrun_list(
+    endpoint = "/v1/events/{event_number}/runs/{run_id}/results",
+    args_type = "RunResultsQuery",
+    data_key = "results"
+)]
+pub struct Finisher {
+    pub event_number: String,
+    #[parkrun(field = "RunId")]
+    pub run_id: String,
+    #[serde(with = "adapters::number")]
+    pub finish_position: u32,
+    #[serde(default, with = "adapters::optional")]
+    pub gender_position: Option<u32>,
+    pub athlete_id: String,
+    #[serde(default)]
+    pub first_name: String,
+    #[serde(default)]
+    pub last_name: String,
+    #[serde(default, with = "adapters::optional")]
+    pub sex: Option<Sex>,
+    #[serde(default, with = "adapters::optional")]
+    pub age_category: Option<AgeCategory>,
+    pub run_time: RunTime,
+    #[serde(default, with = "adapters::optional")]
+    pub age_grading: Option<f32>,
+    #[serde(default)]
+    pub club_id: Option<String>,
+    #[serde(default)]
+    pub club_name: Option<String>,
+    #[serde(default, with = "adapters::flag")]
+    pub was_pb_run: bool,
+    #[serde(default, with = "adapters::flag")]
+    pub first_timer: bool,
+    #[serde(default, with = "adapters::flag")]
+    pub assisted: bool,
+}
+
+impl Finisher {
+    pub fn full_name(&self) -> String {
+        format!("{} {}", self.first_name, self.last_name)
+            .trim()
+            .to_string()
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        self.athlete_id == "0"
+    }
+}
+
+#[parkrun_request_args()]
+pub struct VolunteersQuery {
+    pub athlete_id: String,
+}
+
+/// A role volunteered at an event. Volunteering several roles at the same run gives several of
+/// these, but only one volunteer credit
+#[parkrun_model()]
+#[parkrun_list(
+    endpoint = "/v1/volunteers",
+    args_type = "VolunteersQuery",
+    data_key = "volunteers"
+)]
+pub struct VolunteerRole {
+    #[parkrun(field = "SeriesID")]
+    pub series: Series,
+    pub event_number: String,
+    #[parkrun(field = "RunId")]
+    pub run_id: String,
+    #[serde(with = "adapters::date")]
+    pub event_date: NaiveDate,
+    pub athlete_id: String,
+    #[parkrun(field = "VolunteerRole")]
+    pub role: Role,
+}
+
+impl VolunteerRole {
+    pub fn date(&self) -> NaiveDate {
+        self.event_date
     }
 }
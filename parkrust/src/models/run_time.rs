@@ -0,0 +1,133 @@
+use chrono::Duration;
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, iter::Sum, ops::Add, ops::Div, str::FromStr};
+
+/// A finishing time, sent by the API as `HH:MM:SS`
+///
+/// Parses both `MM:SS` and `HH:MM:SS`, and displays as `MM:SS` unless the time is over an hour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct RunTime {
+    seconds: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid run time {0:?}, expected MM:SS or HH:MM:SS")]
+pub struct ParseRunTimeError(String);
+
+impl RunTime {
+    pub fn from_seconds(seconds: u32) -> Self {
+        RunTime { seconds }
+    }
+
+    pub fn num_seconds(&self) -> u32 {
+        self.seconds
+    }
+
+    pub fn hours(&self) -> u32 {
+        self.seconds / 3600
+    }
+
+    pub fn minutes(&self) -> u32 {
+        (self.seconds / 60) % 60
+    }
+
+    pub fn seconds(&self) -> u32 {
+        self.seconds % 60
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::seconds(self.seconds.into())
+    }
+}
+
+impl FromStr for RunTime {
+    type Err = ParseRunTimeError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let error = || ParseRunTimeError(value.to_string());
+        let parts = value
+            .trim()
+            .split(':')
+            .map(|part| {
+                if part.is_empty() || !part.chars().all(|c| c.is_ascii_digit()) {
+                    return None;
+                }
+                part.parse::<u32>().ok()
+            })
+            .collect::<Option<Vec<u32>>>()
+            .ok_or_else(error)?;
+
+        let (hours, minutes, seconds) = match parts.as_slice() {
+            [minutes, seconds] => (0, *minutes, *seconds),
+            [hours, minutes, seconds] if *minutes < 60 => (*hours, *minutes, *seconds),
+            _ => return Err(error()),
+        };
+        if seconds >= 60 {
+            return Err(error());
+        }
+
+        hours
+            .checked_mul(3600)
+            .and_then(|total| total.checked_add(minutes.checked_mul(60)?))
+            .and_then(|total| total.checked_add(seconds))
+            .map(RunTime::from_seconds)
+            .ok_or_else(error)
+    }
+}
+
+impl fmt::Display for RunTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (hours, minutes, seconds) = (self.hours(), self.minutes(), self.seconds());
+        if hours == 0 {
+            write!(f, "{minutes:0>2}:{seconds:0>2}")
+        } else {
+            write!(f, "{hours:0>2}:{minutes:0>2}:{seconds:0>2}")
+        }
+    }
+}
+
+/// Negative durations are clamped to zero
+impl From<Duration> for RunTime {
+    fn from(duration: Duration) -> Self {
+        RunTime::from_seconds(duration.num_seconds().clamp(0, u32::MAX.into()) as u32)
+    }
+}
+
+impl Add for RunTime {
+    type Output = RunTime;
+
+    fn add(self, other: RunTime) -> RunTime {
+        RunTime::from_seconds(self.seconds + other.seconds)
+    }
+}
+
+impl Div<u32> for RunTime {
+    type Output = RunTime;
+
+    fn div(self, divisor: u32) -> RunTime {
+        RunTime::from_seconds(self.seconds / divisor)
+    }
+}
+
+impl Sum for RunTime {
+    fn sum<I: Iterator<Item = RunTime>>(iter: I) -> RunTime {
+        iter.fold(RunTime::default(), Add::add)
+    }
+}
+
+impl Serialize for RunTime {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Always use the API's format so serialized results can be decoded by anything expecting
+        // the original payload
+        let (hours, minutes, seconds) = (self.hours(), self.minutes(), self.seconds());
+        serializer.collect_str(&format_args!("{hours:0>2}:{minutes:0>2}:{seconds:0>2}"))
+    }
+}
+
+impl<'de> Deserialize<'de> for RunTime {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,106 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{convert::Infallible, fmt, str::FromStr};
+
+/// A volunteer role at an event
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Role {
+    RunDirector,
+    Timekeeper,
+    Marshal,
+    BarcodeScanning,
+    FinishTokens,
+    FunnelManager,
+    TailWalker,
+    Pacer,
+    FirstTimersBriefing,
+    LeadBike,
+    Photographer,
+    PreEventSetup,
+    PostEventClose,
+    TokenSorting,
+    ResultsProcessing,
+    VolunteerCoordinator,
+    RunReportWriter,
+    VisuallyImpairedGuide,
+    /// A role this crate doesn't know about yet
+    Other(String),
+}
+
+impl Role {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Role::RunDirector => "Run Director",
+            Role::Timekeeper => "Timekeeper",
+            Role::Marshal => "Marshal",
+            Role::BarcodeScanning => "Barcode Scanning",
+            Role::FinishTokens => "Finish Tokens",
+            Role::FunnelManager => "Funnel Manager",
+            Role::TailWalker => "Tail Walker",
+            Role::Pacer => "Pacer",
+            Role::FirstTimersBriefing => "First Timers Briefing",
+            Role::LeadBike => "Lead Bike",
+            Role::Photographer => "Photographer",
+            Role::PreEventSetup => "Pre-event Setup",
+            Role::PostEventClose => "Post-event Close Down",
+            Role::TokenSorting => "Token Sorting",
+            Role::ResultsProcessing => "Results Processing",
+            Role::VolunteerCoordinator => "Volunteer Co-ordinator",
+            Role::RunReportWriter => "Run Report Writer",
+            Role::VisuallyImpairedGuide => "Guide Runner",
+            Role::Other(role) => role,
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = Infallible;
+
+    /// Role names are matched ignoring case, spacing and punctuation, as the API isn't consistent
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let key = value
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .collect::<String>()
+            .to_ascii_lowercase();
+        Ok(match key.as_str() {
+            "rundirector" => Role::RunDirector,
+            "timekeeper" | "timekeeping" => Role::Timekeeper,
+            "marshal" => Role::Marshal,
+            "barcodescanning" | "barcodescanner" => Role::BarcodeScanning,
+            "finishtokens" | "finishtokensupport" => Role::FinishTokens,
+            "funnelmanager" => Role::FunnelManager,
+            "tailwalker" => Role::TailWalker,
+            "pacer" => Role::Pacer,
+            "firsttimersbriefing" => Role::FirstTimersBriefing,
+            "leadbike" => Role::LeadBike,
+            "photographer" => Role::Photographer,
+            "preeventsetup" => Role::PreEventSetup,
+            "posteventclose" | "posteventclosedown" => Role::PostEventClose,
+            "tokensorting" => Role::TokenSorting,
+            "resultsprocessing" => Role::ResultsProcessing,
+            "volunteercoordinator" => Role::VolunteerCoordinator,
+            "runreportwriter" => Role::RunReportWriter,
+            "guiderunner" | "viguide" => Role::VisuallyImpairedGuide,
+            _ => Role::Other(value.to_string()),
+        })
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for Role {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Role {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Ok(value.parse().unwrap_or_else(|never| match never {}))
+    }
+}
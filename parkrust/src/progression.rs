@@ -0,0 +1,157 @@
+//! How an athlete has progressed: the PBs they've set over time and the milestones they've reached.
+//!
+//! PBs are worked out from run times rather than the `was_pb_run` and `genuine_pb` flags, so
+//! ladders can be built for any subset of results, such as the results at one event.
+
+use chrono::{Duration, NaiveDate};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use crate::client::requests::chronological;
+use crate::models::parkrun::{RunResult, RunTime, Series, JUNIOR_RUN_MILESTONE, RUN_MILESTONES};
+
+/// Distinct event counts worth celebrating
+pub const EVENT_MILESTONES: [u32; 5] = [10, 20, 50, 100, 250];
+
+/// A result that set a new PB
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pb {
+    pub event_number: String,
+    pub run_id: String,
+    pub date: NaiveDate,
+    pub run_time: RunTime,
+    /// How much faster than the previous PB, `None` for the first run
+    pub improvement: Option<RunTime>,
+}
+
+fn ladder<'a>(results: impl IntoIterator<Item = &'a RunResult>) -> Vec<Pb> {
+    let mut ladder = Vec::<Pb>::new();
+    for result in results {
+        let improvement = match ladder.last() {
+            Some(pb) if result.run_time >= pb.run_time => continue,
+            Some(pb) => Some(RunTime::from_seconds(
+                pb.run_time.num_seconds() - result.run_time.num_seconds(),
+            )),
+            None => None,
+        };
+        ladder.push(Pb {
+            event_number: result.event_number.clone(),
+            run_id: result.run_id.clone(),
+            date: result.event_date,
+            run_time: result.run_time,
+            improvement,
+        });
+    }
+    ladder
+}
+
+/// Every PB over results in `series`, oldest first. Only results of the same series are compared
+/// as junior parkrun is a shorter course
+pub fn pb_ladder(results: &[RunResult], series: Series) -> Vec<Pb> {
+    ladder(
+        chronological(results)
+            .into_iter()
+            .filter(|result| result.series == series),
+    )
+}
+
+/// The PB ladder at each event, by event number
+pub fn event_pb_ladders(results: &[RunResult]) -> BTreeMap<String, Vec<Pb>> {
+    let mut event_results = BTreeMap::<String, Vec<&RunResult>>::new();
+    for result in chronological(results) {
+        event_results
+            .entry(result.event_number.clone())
+            .or_default()
+            .push(result);
+    }
+    event_results
+        .into_iter()
+        .map(|(event_number, results)| (event_number, ladder(results)))
+        .collect()
+}
+
+/// The current PB in `series`
+pub fn current_pb(results: &[RunResult], series: Series) -> Option<Pb> {
+    pb_ladder(results, series).pop()
+}
+
+/// Time from the last PB in `series` to `today`, `None` without any results in the series
+pub fn time_since_last_pb(
+    results: &[RunResult],
+    series: Series,
+    today: NaiveDate,
+) -> Option<Duration> {
+    Some(today - current_pb(results, series)?.date)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Milestone {
+    FirstRun,
+    /// Reached a run count in [`RUN_MILESTONES`], counting 5k runs
+    Runs(u32),
+    /// Reached [`JUNIOR_RUN_MILESTONE`] junior parkruns
+    JuniorRuns(u32),
+    /// Ran at a new event, taking the distinct event count to a number in [`EVENT_MILESTONES`]
+    Events(u32),
+}
+
+impl fmt::Display for Milestone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Milestone::FirstRun => f.write_str("First parkrun"),
+            Milestone::Runs(count) => write!(f, "{count} club"),
+            Milestone::JuniorRuns(count) => write!(f, "{count} junior parkruns"),
+            Milestone::Events(count) => write!(f, "{count} different events"),
+        }
+    }
+}
+
+/// A milestone and the result it was reached at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MilestoneReached {
+    pub milestone: Milestone,
+    pub event_number: String,
+    pub run_id: String,
+    pub date: NaiveDate,
+}
+
+/// Every milestone reached over the results, oldest first
+pub fn milestones(results: &[RunResult]) -> Vec<MilestoneReached> {
+    let mut reached = Vec::new();
+    let mut runs = 0;
+    let mut junior_runs = 0;
+    let mut events = HashSet::<&str>::new();
+
+    for (index, result) in chronological(results).into_iter().enumerate() {
+        let mut reach = |milestone| {
+            reached.push(MilestoneReached {
+                milestone,
+                event_number: result.event_number.clone(),
+                run_id: result.run_id.clone(),
+                date: result.event_date,
+            })
+        };
+
+        if index == 0 {
+            reach(Milestone::FirstRun);
+        }
+        if result.series == Series::Junior {
+            junior_runs += 1;
+            if junior_runs == JUNIOR_RUN_MILESTONE {
+                reach(Milestone::JuniorRuns(junior_runs));
+            }
+        } else {
+            runs += 1;
+            if RUN_MILESTONES.contains(&runs) {
+                reach(Milestone::Runs(runs));
+            }
+        }
+        if events.insert(&result.event_number) {
+            let count = events.len() as u32;
+            if EVENT_MILESTONES.contains(&count) {
+                reach(Milestone::Events(count));
+            }
+        }
+    }
+    reached
+}
@@ -0,0 +1,228 @@
+use chrono::NaiveDate;
+use serde_json::{json, Value};
+
+use crate::models::parkrun::{
+    AgeCategory, Country, Event, EventStatus, RunResult, RunTime, Series,
+};
+use crate::testing::ATHLETE_ID;
+
+/// Builds [`RunResult`]s for tests, starting from a 25 minute 5k finished 10th at event `1` on
+/// 2023-01-07. Results are built from JSON, so they decode the same way as API responses
+///
+/// ```
+/// use parkrust::models::parkrun::Series;
+/// use parkrust::testing::RunResultBuilder;
+///
+/// let result = RunResultBuilder::new()
+///     .series(Series::Junior)
+///     .run_time("10:30")
+///     .build();
+/// assert_eq!(result.run_time.to_string(), "10:30");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunResultBuilder {
+    json: Value,
+}
+
+impl Default for RunResultBuilder {
+    fn default() -> Self {
+        RunResultBuilder {
+            json: json!({
+                "SeriesID": "1",
+                "EventNumber": "1",
+                "RunId": "1",
+                "FinishPosition": "10",
+                "GenderPosition": "5",
+                "EventDate": "2023-01-07",
+                "AthleteID": ATHLETE_ID,
+                "RunTime": "00:25:00",
+                "WasPbRun": "0",
+                "AgeGrading": "60.00",
+                "AgeCategory": "SW25-29",
+                "FirstTimer": "0",
+                "GenuinePB": "0",
+                "Updated": "2023-01-07 12:03:11",
+            }),
+        }
+    }
+}
+
+impl RunResultBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a field to a raw JSON value, e.g. to build a malformed payload
+    pub fn field(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.json[key] = value.into();
+        self
+    }
+
+    /// Remove a field from the payload
+    pub fn without(mut self, key: &str) -> Self {
+        if let Some(object) = self.json.as_object_mut() {
+            object.remove(key);
+        }
+        self
+    }
+
+    pub fn series(self, series: Series) -> Self {
+        let series = serde_json::to_value(series).unwrap();
+        self.field("SeriesID", series)
+    }
+
+    pub fn event_number(self, event_number: &str) -> Self {
+        self.field("EventNumber", event_number)
+    }
+
+    pub fn run_id(self, run_id: &str) -> Self {
+        self.field("RunId", run_id)
+    }
+
+    pub fn finish_position(self, finish_position: u32) -> Self {
+        self.field("FinishPosition", finish_position.to_string())
+    }
+
+    pub fn gender_position(self, gender_position: u32) -> Self {
+        self.field("GenderPosition", gender_position.to_string())
+    }
+
+    pub fn event_date(self, event_date: NaiveDate) -> Self {
+        self.field("EventDate", event_date.format("%Y-%m-%d").to_string())
+    }
+
+    pub fn athlete_id(self, athlete_id: &str) -> Self {
+        self.field("AthleteID", athlete_id)
+    }
+
+    /// Panics if `run_time` isn't a valid time such as `25:00` or `01:02:03`
+    pub fn run_time(self, run_time: &str) -> Self {
+        let run_time = serde_json::to_value(run_time.parse::<RunTime>().unwrap()).unwrap();
+        self.field("RunTime", run_time)
+    }
+
+    pub fn age_grading(self, age_grading: f32) -> Self {
+        self.field("AgeGrading", format!("{age_grading:.2}"))
+    }
+
+    /// Panics if `age_category` isn't a valid category such as `VW40-44`
+    pub fn age_category(self, age_category: &str) -> Self {
+        let age_category = age_category.parse::<AgeCategory>().unwrap();
+        self.field("AgeCategory", age_category.to_string())
+    }
+
+    pub fn first_timer(self, first_timer: bool) -> Self {
+        self.field("FirstTimer", flag(first_timer))
+    }
+
+    pub fn was_pb_run(self, was_pb_run: bool) -> Self {
+        self.field("WasPbRun", flag(was_pb_run))
+    }
+
+    pub fn genuine_pb(self, genuine_pb: bool) -> Self {
+        self.field("GenuinePB", flag(genuine_pb))
+    }
+
+    /// The JSON payload as the API would send it
+    pub fn json(&self) -> Value {
+        self.json.clone()
+    }
+
+    /// Panics if the payload doesn't decode, use [`json`](Self::json) to test malformed payloads
+    pub fn build(&self) -> RunResult {
+        serde_json::from_value(self.json()).unwrap()
+    }
+}
+
+/// Builds [`Event`]s for tests, starting from a live 5k event `1` named Bushy Park in the UK.
+/// Events are built from JSON, so they decode the same way as API responses
+///
+/// ```
+/// use parkrust::testing::EventBuilder;
+///
+/// let event = EventBuilder::new()
+///     .event_number("12")
+///     .event_short_name("Wimbledon Common")
+///     .build();
+/// assert!(event.is_live());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventBuilder {
+    json: Value,
+}
+
+impl Default for EventBuilder {
+    fn default() -> Self {
+        EventBuilder {
+            json: json!({
+                "EventNumber": "1",
+                "EventName": "bushy",
+                "EventShortName": "Bushy Park",
+                "EventLongName": "Bushy parkrun",
+                "EventLocation": "Bushy Park, Teddington",
+                "CountryCode": "97",
+                "PreferredLanguage": "en-gb",
+                "SeriesID": "1",
+                "StatusLive": "1",
+                "AnniversarySaturdayOfMonth": "1",
+                "EventStatus": "Live",
+            }),
+        }
+    }
+}
+
+impl EventBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a field to a raw JSON value, e.g. to build a malformed payload
+    pub fn field(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.json[key] = value.into();
+        self
+    }
+
+    pub fn event_number(self, event_number: &str) -> Self {
+        self.field("EventNumber", event_number)
+    }
+
+    pub fn event_short_name(self, event_short_name: &str) -> Self {
+        self.field("EventShortName", event_short_name)
+    }
+
+    pub fn country(self, country: Country) -> Self {
+        let country = serde_json::to_value(country).unwrap();
+        self.field("CountryCode", country)
+    }
+
+    pub fn series(self, series: Series) -> Self {
+        let series = serde_json::to_value(series).unwrap();
+        self.field("SeriesID", series)
+    }
+
+    /// Also sets `StatusLive` to match, as the API does
+    pub fn event_status(self, event_status: EventStatus) -> Self {
+        let status_live = flag(event_status == EventStatus::Live);
+        let event_status = serde_json::to_value(event_status).unwrap();
+        self.field("EventStatus", event_status)
+            .field("StatusLive", status_live)
+    }
+
+    /// The JSON payload as the API would send it
+    pub fn json(&self) -> Value {
+        self.json.clone()
+    }
+
+    /// Panics if the payload doesn't decode, use [`json`](Self::json) to test malformed payloads
+    pub fn build(&self) -> Event {
+        serde_json::from_value(self.json()).unwrap()
+    }
+}
+
+fn flag(value: bool) -> &'static str {
+    if value {
+        "1"
+    } else {
+        "0"
+    }
+}
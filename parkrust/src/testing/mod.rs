@@ -0,0 +1,524 @@
+//! An offline mock of the parkrun API for tests, enabled with the `testing` feature.
+//!
+//! [`MockServer`] serves `/user_auth.php`, `/auth/refresh`, `/v1/me`, `/v1/athletes/{id}`,
+//! `/v1/clubs/{id}`, `/v1/clubs/{id}/members`, `/v1/events`, `/v1/results`, `/v1/volunteers` and
+//! `/v1/events/{id}/runs/{run}/results` from fixture data, paging list responses the same way the
+//! real API does. [`MockServer::start_at`] serves the same paths under a base path.
+//!
+//! ```no_run
+//! # async fn example() -> parkrust::Result<()> {
+//! use parkrust::models::parkrun::{Listable, ResultsQuery, RunResult};
+//! use parkrust::testing::{MockServer, ATHLETE_ID, PASSWORD};
+//!
+//! let server = MockServer::start().await;
+//! let mut client = server.client().authenticate(ATHLETE_ID, PASSWORD).await?;
+//! let results = RunResult::list(
+//!     ResultsQuery {
+//!         athlete_id: String::from(ATHLETE_ID),
+//!     },
+//!     &mut client,
+//! )
+//! .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+use crate::client::ParkrunClient;
+use crate::models::parkrun::{Athlete, Club, Event, Finisher, RunResult, VolunteerRole};
+
+mod builders;
+
+pub use builders::{EventBuilder, RunResultBuilder};
+
+/// The athlete id the mock server accepts
+pub const ATHLETE_ID: &str = "123456";
+/// The password the mock server accepts
+pub const PASSWORD: &str = "password";
+/// Default number of items in each page of a list response
+pub const DEFAULT_PAGE_SIZE: usize = 3;
+
+const EVENTS_FIXTURE: &str = include_str!("fixtures/events.json");
+const RESULTS_FIXTURE: &str = include_str!("fixtures/results.json");
+const ATHLETES_FIXTURE: &str = include_str!("fixtures/athletes.json");
+const FINISHERS_FIXTURE: &str = include_str!("fixtures/finishers.json");
+const CLUBS_FIXTURE: &str = include_str!("fixtures/clubs.json");
+const VOLUNTEERS_FIXTURE: &str = include_str!("fixtures/volunteers.json");
+
+/// How the mock server writes `next` page links
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NextLinkStyle {
+    /// `./v1/results?...`, as the real API does
+    #[default]
+    DotRelative,
+    /// `/v1/results?...`
+    RootRelative,
+    /// `http://127.0.0.1:1234/v1/results?...&access_token=...`, including the access token the
+    /// page was requested with
+    Absolute,
+    /// Always link back to the first page
+    Loop,
+}
+
+/// A request received by the mock server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedRequest {
+    pub method: Method,
+    /// The path without the server's base path e.g. `/v1/results`
+    pub path: String,
+    pub query: BTreeMap<String, String>,
+    /// Header names are lowercase
+    pub headers: BTreeMap<String, String>,
+}
+
+struct State {
+    athletes: Vec<Value>,
+    clubs: Vec<Value>,
+    events: Vec<Value>,
+    results: Vec<Value>,
+    finishers: Vec<Value>,
+    volunteers: Vec<Value>,
+    page_size: usize,
+    next_link_style: NextLinkStyle,
+    url: String,
+    base_path: String,
+    access_tokens: HashSet<String>,
+    refresh_tokens: HashSet<String>,
+    issued_tokens: usize,
+    requests: Vec<RecordedRequest>,
+}
+
+/// A local HTTP server emulating the parkrun API. The server shuts down when dropped
+pub struct MockServer {
+    state: Arc<Mutex<State>>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockServer {
+    /// Start a server on a random local port, serving the built in fixtures
+    pub async fn start() -> Self {
+        Self::start_at("/").await
+    }
+
+    /// Start a server serving the API under `base_path` e.g. `/parkrun/`, as if behind a proxy.
+    /// Requests outside the base path are not found
+    pub async fn start_at(base_path: &str) -> Self {
+        let base_path = format!("/{}/", base_path.trim_matches('/')).replace("//", "/");
+        let state = Arc::new(Mutex::new(State {
+            athletes: serde_json::from_str(ATHLETES_FIXTURE).unwrap(),
+            clubs: serde_json::from_str(CLUBS_FIXTURE).unwrap(),
+            events: serde_json::from_str(EVENTS_FIXTURE).unwrap(),
+            results: serde_json::from_str(RESULTS_FIXTURE).unwrap(),
+            finishers: serde_json::from_str(FINISHERS_FIXTURE).unwrap(),
+            volunteers: serde_json::from_str(VOLUNTEERS_FIXTURE).unwrap(),
+            page_size: DEFAULT_PAGE_SIZE,
+            next_link_style: NextLinkStyle::default(),
+            url: String::new(),
+            base_path: base_path.clone(),
+            access_tokens: HashSet::new(),
+            refresh_tokens: HashSet::new(),
+            issued_tokens: 0,
+            requests: Vec::new(),
+        }));
+
+        let service_state = state.clone();
+        let make_service = make_service_fn(move |_| {
+            let state = service_state.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    handle_request(state.clone(), request)
+                }))
+            }
+        });
+
+        let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+        let addr = server.local_addr();
+        state.lock().unwrap().url = format!("http://{addr}{base_path}");
+        let (shutdown, shutdown_signal) = oneshot::channel::<()>();
+        tokio::spawn(server.with_graceful_shutdown(async {
+            shutdown_signal.await.ok();
+        }));
+
+        MockServer {
+            state,
+            shutdown: Some(shutdown),
+        }
+    }
+
+    pub fn url(&self) -> String {
+        self.state.lock().unwrap().url.clone()
+    }
+
+    /// A client pointing at this server
+    pub fn client(&self) -> ParkrunClient {
+        ParkrunClient::builder()
+            .base_url(self.url())
+            .build()
+            .unwrap()
+    }
+
+    /// Every request the server has received, oldest first
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Requests the server has received for `path`, oldest first
+    pub fn requests_to(&self, path: &str) -> Vec<RecordedRequest> {
+        self.requests()
+            .into_iter()
+            .filter(|request| request.path == path)
+            .collect()
+    }
+
+    pub fn set_page_size(&self, page_size: usize) {
+        self.state.lock().unwrap().page_size = page_size;
+    }
+
+    pub fn set_next_link_style(&self, next_link_style: NextLinkStyle) {
+        self.state.lock().unwrap().next_link_style = next_link_style;
+    }
+
+    /// Set the athletes served by `/v1/athletes/{id}` and as club members
+    pub fn set_athletes(&self, athletes: &[Athlete]) {
+        self.state.lock().unwrap().athletes = to_values(athletes);
+    }
+
+    pub fn set_clubs(&self, clubs: &[Club]) {
+        self.state.lock().unwrap().clubs = to_values(clubs);
+    }
+
+    pub fn set_events(&self, events: &[Event]) {
+        self.state.lock().unwrap().events = to_values(events);
+    }
+
+    pub fn set_results(&self, results: &[RunResult]) {
+        self.state.lock().unwrap().results = to_values(results);
+    }
+
+    /// Set the finishers of every run, each is served for the run matching its event number and
+    /// run id
+    pub fn set_finishers(&self, finishers: &[Finisher]) {
+        self.state.lock().unwrap().finishers = to_values(finishers);
+    }
+
+    pub fn set_volunteers(&self, volunteers: &[VolunteerRole]) {
+        self.state.lock().unwrap().volunteers = to_values(volunteers);
+    }
+
+    /// Reject every access token issued so far, as if they had expired early
+    pub fn revoke_access_tokens(&self) {
+        self.state.lock().unwrap().access_tokens.clear();
+    }
+
+    /// Reject every refresh token issued so far
+    pub fn revoke_refresh_tokens(&self) {
+        self.state.lock().unwrap().refresh_tokens.clear();
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            shutdown.send(()).ok();
+        }
+    }
+}
+
+fn to_values<T: Serialize>(items: &[T]) -> Vec<Value> {
+    items
+        .iter()
+        .map(|item| serde_json::to_value(item).unwrap())
+        .collect()
+}
+
+fn parse_query(query: &[u8]) -> BTreeMap<String, String> {
+    url::form_urlencoded::parse(query).into_owned().collect()
+}
+
+fn json_response(status: StatusCode, body: Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+async fn handle_request(
+    state: Arc<Mutex<State>>,
+    request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    let method = request.method().clone();
+    let full_path = request.uri().path().to_string();
+    let headers = request
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+    let raw_query = request.uri().query().unwrap_or_default().to_string();
+    let query = parse_query(raw_query.as_bytes());
+    let access_token_count = url::form_urlencoded::parse(raw_query.as_bytes())
+        .filter(|(key, _)| key == "access_token")
+        .count();
+    let has_basic_auth = request
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("Basic "));
+    let body = hyper::body::to_bytes(request.into_body())
+        .await
+        .unwrap_or_default();
+    let form = parse_query(&body);
+
+    let mut state = state.lock().unwrap();
+    let Some(path) = full_path
+        .strip_prefix(state.base_path.trim_end_matches('/'))
+        .filter(|path| path.starts_with('/'))
+        .map(str::to_string)
+    else {
+        return Ok(json_response(
+            StatusCode::NOT_FOUND,
+            json!({ "error": "not_found" }),
+        ));
+    };
+    state.requests.push(RecordedRequest {
+        method: method.clone(),
+        path: path.clone(),
+        query: query.clone(),
+        headers,
+    });
+
+    let response = match (method, path.as_str()) {
+        (Method::POST, "/user_auth.php" | "/auth/refresh") if !has_basic_auth => json_response(
+            StatusCode::UNAUTHORIZED,
+            json!({ "error": "invalid_client" }),
+        ),
+        (Method::POST, "/user_auth.php") => state.authenticate(&form),
+        (Method::POST, "/auth/refresh") => state.refresh(&form),
+        (Method::GET, _) if access_token_count > 1 => json_response(
+            StatusCode::BAD_REQUEST,
+            json!({ "error": "duplicate access_token" }),
+        ),
+        (Method::GET, _)
+            if !query
+                .get("access_token")
+                .is_some_and(|token| state.access_tokens.contains(token)) =>
+        {
+            json_response(
+                StatusCode::UNAUTHORIZED,
+                json!({ "error": "invalid_token" }),
+            )
+        }
+        (Method::GET, "/v1/me") => state.athlete(ATHLETE_ID),
+        (Method::GET, "/v1/events") => {
+            let events = state.events.clone();
+            state.list("v1/events", "Events", events, &query)
+        }
+        (Method::GET, "/v1/results") => {
+            let athlete_id = query.get("athleteId").cloned().unwrap_or_default();
+            let results = state
+                .results
+                .iter()
+                .filter(|result| result["AthleteID"] == athlete_id.as_str())
+                .cloned()
+                .collect();
+            state.list("v1/results", "Results", results, &query)
+        }
+        (Method::GET, "/v1/volunteers") => {
+            let athlete_id = query.get("athleteId").cloned().unwrap_or_default();
+            let volunteers = state
+                .volunteers
+                .iter()
+                .filter(|volunteer| volunteer["AthleteID"] == athlete_id.as_str())
+                .cloned()
+                .collect();
+            state.list("v1/volunteers", "Volunteers", volunteers, &query)
+        }
+        (Method::GET, path) if run_results_path(path).is_some() => {
+            let (event_number, run_id) = run_results_path(path).unwrap();
+            let finishers = state
+                .finishers
+                .iter()
+                .filter(|finisher| {
+                    finisher["EventNumber"] == event_number && finisher["RunId"] == run_id
+                })
+                .cloned()
+                .collect();
+            state.list(&path[1..], "Results", finishers, &query)
+        }
+        (Method::GET, path) if path.starts_with("/v1/clubs/") => {
+            let club_path = &path["/v1/clubs/".len()..];
+            match club_path.strip_suffix("/members") {
+                Some(club_id) => {
+                    let members = state
+                        .athletes
+                        .iter()
+                        .filter(|athlete| athlete["ClubID"] == club_id)
+                        .cloned()
+                        .collect();
+                    state.list(&path[1..], "Members", members, &query)
+                }
+                None => state.club(club_path),
+            }
+        }
+        (Method::GET, path) if path.starts_with("/v1/athletes/") => {
+            state.athlete(&path["/v1/athletes/".len()..])
+        }
+        _ => json_response(StatusCode::NOT_FOUND, json!({ "error": "not_found" })),
+    };
+    Ok(response)
+}
+
+/// The event number and run id from a `/v1/events/{id}/runs/{run}/results` path
+fn run_results_path(path: &str) -> Option<(&str, &str)> {
+    let rest = path.strip_prefix("/v1/events/")?;
+    let (event_number, rest) = rest.split_once("/runs/")?;
+    let run_id = rest.strip_suffix("/results")?;
+    Some((event_number, run_id))
+}
+
+impl State {
+    fn issue_token(&mut self, prefix: &str) -> String {
+        self.issued_tokens += 1;
+        format!("{prefix}-{}", self.issued_tokens)
+    }
+
+    fn authenticate(&mut self, form: &BTreeMap<String, String>) -> Response<Body> {
+        let field = |key: &str| form.get(key).map(String::as_str);
+        if field("grant_type") != Some("password")
+            || field("username") != Some(ATHLETE_ID)
+            || field("password") != Some(PASSWORD)
+        {
+            return json_response(
+                StatusCode::BAD_REQUEST,
+                json!({
+                    "error": "invalid_grant",
+                    "error_description": "Invalid username and password combination",
+                }),
+            );
+        }
+
+        let access_token = self.issue_token("access");
+        let refresh_token = self.issue_token("refresh");
+        self.access_tokens.insert(access_token.clone());
+        self.refresh_tokens.insert(refresh_token.clone());
+        json_response(
+            StatusCode::OK,
+            json!({
+                "access_token": access_token,
+                "refresh_token": refresh_token,
+                "expires_in": "3600",
+                "token_type": "bearer",
+                "scope": "app",
+            }),
+        )
+    }
+
+    fn refresh(&mut self, form: &BTreeMap<String, String>) -> Response<Body> {
+        let refresh_token = form.get("refresh_token");
+        if form.get("grant_type").map(String::as_str) != Some("refresh_token")
+            || !refresh_token.is_some_and(|token| self.refresh_tokens.contains(token))
+        {
+            return json_response(
+                StatusCode::UNAUTHORIZED,
+                json!({ "error": "invalid_grant" }),
+            );
+        }
+
+        let access_token = self.issue_token("access");
+        self.access_tokens.insert(access_token.clone());
+        json_response(
+            StatusCode::OK,
+            json!({ "access_token": access_token, "expires_in": 3600 }),
+        )
+    }
+
+    fn athlete(&self, athlete_id: &str) -> Response<Body> {
+        let athletes = self
+            .athletes
+            .iter()
+            .filter(|athlete| athlete["AthleteID"] == athlete_id)
+            .cloned()
+            .collect::<Vec<Value>>();
+        json_response(
+            StatusCode::OK,
+            json!({ "data": { "Athletes": athletes }, "links": [] }),
+        )
+    }
+
+    fn club(&self, club_id: &str) -> Response<Body> {
+        let clubs = self
+            .clubs
+            .iter()
+            .filter(|club| club["ClubID"] == club_id)
+            .cloned()
+            .collect::<Vec<Value>>();
+        json_response(
+            StatusCode::OK,
+            json!({ "data": { "Clubs": clubs }, "links": [] }),
+        )
+    }
+
+    /// Respond with a page of `items`, linking to the next page in the same way as the API
+    fn list(
+        &self,
+        endpoint: &str,
+        data_key: &str,
+        items: Vec<Value>,
+        query: &BTreeMap<String, String>,
+    ) -> Response<Body> {
+        let offset = query
+            .get("offset")
+            .and_then(|offset| offset.parse().ok())
+            .unwrap_or(0);
+        let limit = query
+            .get("limit")
+            .and_then(|limit| limit.parse().ok())
+            .unwrap_or(self.page_size)
+            .max(1);
+        let page = items
+            .iter()
+            .skip(offset)
+            .take(limit)
+            .cloned()
+            .collect::<Vec<Value>>();
+
+        let link = |offset: usize| {
+            let mut query = query.clone();
+            if self.next_link_style != NextLinkStyle::Absolute {
+                query.remove("access_token");
+            }
+            query.insert(String::from("offset"), offset.to_string());
+            query.insert(String::from("limit"), limit.to_string());
+            let query = url::form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(query)
+                .finish();
+            match self.next_link_style {
+                NextLinkStyle::RootRelative => format!("/{endpoint}?{query}"),
+                NextLinkStyle::Absolute => format!("{}{endpoint}?{query}", self.url),
+                _ => format!("./{endpoint}?{query}"),
+            }
+        };
+        let mut links = vec![json!({ "rel": "self", "href": link(offset) })];
+        if offset + limit < items.len() {
+            let next_offset = match self.next_link_style {
+                NextLinkStyle::Loop => 0,
+                _ => offset + limit,
+            };
+            links.push(json!({ "rel": "next", "href": link(next_offset) }));
+        }
+
+        json_response(
+            StatusCode::OK,
+            json!({ "data": { data_key: page }, "links": links }),
+        )
+    }
+}
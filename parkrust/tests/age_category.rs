@@ -0,0 +1,91 @@
+use parkrust::models::parkrun::{AgeCategory, AgeClass, Sex, YOUNGEST_JUNIOR_AGE};
+use serde_json::json;
+
+fn category(code: &str) -> AgeCategory {
+    code.parse().unwrap()
+}
+
+#[test]
+fn age_ranges_are_parsed() {
+    let category = category("VW50-54");
+    assert_eq!(category.sex, Sex::Female);
+    assert_eq!(category.class, AgeClass::Veteran);
+    assert_eq!((category.min_age, category.max_age), (Some(50), Some(54)));
+    assert!(category.contains_age(50));
+    assert!(category.contains_age(54));
+    assert!(!category.contains_age(55));
+}
+
+#[test]
+fn youngest_juniors_have_no_lower_bound() {
+    let category = category("JM10");
+    assert!(category.is_junior());
+    assert_eq!((category.min_age, category.max_age), (None, Some(10)));
+    assert!(category.contains_age(YOUNGEST_JUNIOR_AGE));
+    assert!(category.contains_age(8));
+    assert!(category.contains_age(10));
+    assert!(!category.contains_age(11));
+    assert_eq!(category.to_string(), "JM10");
+}
+
+#[test]
+fn open_ended_categories() {
+    for code in ["VM100", "VM100+"] {
+        let category = category(code);
+        assert_eq!((category.min_age, category.max_age), (Some(100), None));
+        assert!(category.contains_age(104));
+        assert!(!category.contains_age(99));
+        assert_eq!(category.to_string(), code);
+    }
+    assert!(!category("VM100").open_ended_plus);
+    assert!(category("VM100+").open_ended_plus);
+}
+
+#[test]
+fn wheelchair_categories() {
+    let category = category("mwc");
+    assert!(category.is_wheelchair());
+    assert_eq!(category.sex, Sex::Male);
+    assert_eq!((category.min_age, category.max_age), (None, None));
+    assert!(!category.contains_age(30));
+    assert_eq!(category.to_string(), "MWC");
+}
+
+#[test]
+fn codes_round_trip() {
+    for code in [
+        "JM10", "JW10", "JM11-14", "JW15-17", "SM18-19", "SW25-29", "VM35-39", "VW95-99", "VM100",
+        "VM100+", "VW100+", "MWC", "WWC",
+    ] {
+        assert_eq!(category(code).to_string(), code);
+        assert_eq!(category(code).code(), code);
+    }
+}
+
+#[test]
+fn invalid_codes_are_rejected() {
+    for code in [
+        "", "SM", "XM25-29", "SX25-29", "SM25-", "SM-29", "SM29-25", "SMabc", "VM100++", "XWC",
+        "SM256",
+    ] {
+        assert!(
+            code.parse::<AgeCategory>().is_err(),
+            "{code:?} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn serde_round_trip() {
+    for code in ["JW10", "SM25-29", "VM100", "VW100+", "WWC"] {
+        let category: AgeCategory = serde_json::from_value(json!(code)).unwrap();
+        assert_eq!(category, self::category(code));
+        assert_eq!(serde_json::to_value(category).unwrap(), json!(code));
+    }
+    assert!(serde_json::from_value::<AgeCategory>(json!("SM")).is_err());
+    assert!(serde_json::from_value::<AgeCategory>(json!(25)).is_err());
+
+    let sex: Sex = serde_json::from_value(json!("W")).unwrap();
+    assert_eq!(sex, Sex::Female);
+    assert_eq!(serde_json::to_value(Sex::Male).unwrap(), json!("M"));
+}
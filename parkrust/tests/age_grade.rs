@@ -0,0 +1,200 @@
+use parkrust::age_grade::{
+    age_equivalent_time, age_factor, age_grade, age_grade_range, open_standard, time_for_age_grade,
+    AgeGradeTable,
+};
+use parkrust::models::parkrun::{AgeCategory, RunTime, Sex};
+use parkrust::testing::RunResultBuilder;
+
+fn time(value: &str) -> RunTime {
+    value.parse().unwrap()
+}
+
+#[test]
+fn open_standard_grades_one_hundred_percent() {
+    let standard = RunTime::from(open_standard(Sex::Male, 5000));
+
+    assert_eq!(standard.to_string(), "12:59");
+    let grade = age_grade(Sex::Male, 25, 5000, standard).unwrap();
+    assert!((grade - 100.0).abs() < 0.1, "{grade}");
+    // Junior parkrun's 2k sits between the mile and 5k standards
+    let junior = open_standard(Sex::Female, 2000).num_seconds();
+    assert!((254..884).contains(&junior), "{junior}");
+}
+
+#[test]
+fn built_in_tables_cover_every_age_and_distance() {
+    let table = AgeGradeTable::road();
+
+    for sex in [Sex::Male, Sex::Female] {
+        for distance in [1609, 5000, 8000, 10000, 15000, 16093, 21097, 42195] {
+            assert!(table.open_standard(sex, distance).num_seconds() > 0);
+            for age in 5..=100 {
+                let factor = table.age_factor(sex, age, distance);
+                assert!(0.0 < factor && factor <= 1.0, "{sex} {age} {distance}");
+            }
+        }
+    }
+    assert_eq!(
+        age_factor(Sex::Male, 40, 5000),
+        table.age_factor(Sex::Male, 40, 5000)
+    );
+}
+
+#[test]
+fn factors_fall_with_age() {
+    assert_eq!(age_factor(Sex::Female, 27, 5000), 1.0);
+    assert!(age_factor(Sex::Female, 52, 5000) < age_factor(Sex::Female, 51, 5000));
+    assert!(age_factor(Sex::Male, 10, 2000) < age_factor(Sex::Male, 15, 2000));
+    assert!(age_factor(Sex::Male, 100, 5000) > 0.0);
+    // Single years of age, rather than only the tabulated ages
+    assert!(age_factor(Sex::Male, 41, 5000) < age_factor(Sex::Male, 40, 5000));
+}
+
+#[test]
+fn grade_improves_at_next_birthday() {
+    let run_time = time("24:30");
+
+    let now = age_grade(Sex::Female, 54, 5000, run_time).unwrap();
+    let next_birthday = age_grade(Sex::Female, 55, 5000, run_time).unwrap();
+
+    assert!(next_birthday > now);
+    assert!(age_equivalent_time(Sex::Female, 54, 5000, run_time) < run_time);
+    let target = time_for_age_grade(Sex::Female, 54, 5000, now);
+    assert!(target.num_seconds().abs_diff(run_time.num_seconds()) <= 1);
+    assert_eq!(
+        age_grade(Sex::Female, 54, 5000, RunTime::from_seconds(0)),
+        None
+    );
+}
+
+#[test]
+fn range_covers_the_category() {
+    let category: AgeCategory = "VM50-54".parse().unwrap();
+
+    let range = age_grade_range(category, 5000, time("20:00")).unwrap();
+
+    assert_eq!(
+        *range.start(),
+        age_grade(Sex::Male, 50, 5000, time("20:00")).unwrap()
+    );
+    assert_eq!(
+        *range.end(),
+        age_grade(Sex::Male, 54, 5000, time("20:00")).unwrap()
+    );
+    assert_eq!(
+        age_grade_range("MWC".parse().unwrap(), 5000, time("20:00")),
+        None
+    );
+}
+
+#[test]
+fn api_age_grading_is_plausible() {
+    let result = RunResultBuilder::new()
+        .run_time("21:30")
+        .age_grading(72.40)
+        .age_category("VW40-44")
+        .build();
+
+    let range = result.expected_age_grading().unwrap();
+
+    assert!(range.start() - 1.0 <= result.age_grading && result.age_grading <= range.end() + 1.0);
+}
+
+// Round numbers in the layout of the WMA tables, so the expected grades can be worked out by hand.
+// These are not real WMA factors
+const MALE_TABLE: &str = "\
+# Test table
+Age,5 km,10 km
+OC,800,1600
+49,0.9000,0.9100
+50,0.8000,0.8200
+51,0.7500,0.7700
+";
+
+const FEMALE_TABLE: &str = "\
+Age,Mile,5000,Marathon
+OC sec,4:00,15:00,2:20:00
+50,0.9000,0.8000,0.7000
+";
+
+fn table() -> AgeGradeTable {
+    AgeGradeTable::from_csv(MALE_TABLE, FEMALE_TABLE).unwrap()
+}
+
+#[test]
+fn tables_grade_with_the_factor_for_the_age_and_distance() {
+    let table = table();
+
+    assert_eq!(table.age_factor(Sex::Male, 50, 5000), 0.8);
+    assert_eq!(table.age_factor(Sex::Male, 50, 10000), 0.82);
+    assert_eq!(table.age_factor(Sex::Female, 50, 42195), 0.7);
+    // 800s / 0.8 is an age standard of 1000s, run in 1250s that's 80%
+    assert_eq!(table.age_standard(Sex::Male, 50, 5000).num_seconds(), 1000);
+    assert_eq!(
+        table.age_grade(Sex::Male, 50, 5000, RunTime::from_seconds(1250)),
+        Some(80.0)
+    );
+    // 1600s / 0.82 over 2500s
+    let grade = table
+        .age_grade(Sex::Male, 50, 10000, RunTime::from_seconds(2500))
+        .unwrap();
+    assert!((grade - 78.05).abs() < 0.01, "{grade}");
+    assert_eq!(
+        table.time_for_age_grade(Sex::Male, 51, 5000, 80.0),
+        RunTime::from_seconds(1333)
+    );
+    assert_eq!(
+        table.age_equivalent_time(Sex::Male, 50, 5000, RunTime::from_seconds(1250)),
+        RunTime::from_seconds(1000)
+    );
+    assert_eq!(
+        table.open_standard(Sex::Female, 5000).num_seconds(),
+        15 * 60
+    );
+}
+
+#[test]
+fn tables_clamp_ages_and_interpolate_distances() {
+    let table = table();
+
+    assert_eq!(table.age_factor(Sex::Male, 20, 5000), 0.9);
+    assert_eq!(table.age_factor(Sex::Male, 90, 5000), 0.75);
+    // Factors are not extrapolated past the table's distances
+    assert_eq!(table.age_factor(Sex::Male, 50, 2000), 0.8);
+    let factor = table.age_factor(Sex::Male, 50, 7000);
+    assert!(0.8 < factor && factor < 0.82, "{factor}");
+    let standard = table.open_standard(Sex::Male, 7000).num_seconds();
+    assert!((800..1600).contains(&standard), "{standard}");
+
+    let range = table
+        .age_grade_range(
+            "VM50-54".parse().unwrap(),
+            5000,
+            RunTime::from_seconds(1250),
+        )
+        .unwrap();
+    assert_eq!(*range.start(), 80.0);
+    let highest = 800.0 / 0.75 / 1250.0 * 100.0;
+    assert!((range.end() - highest).abs() < 0.01, "{range:?}");
+}
+
+#[test]
+fn invalid_tables_are_rejected() {
+    let male_with = |csv: &str| AgeGradeTable::from_csv(csv, FEMALE_TABLE);
+
+    let error = male_with("Age,5 km\nOC,800\n50,0.8\n52,0.7\n").unwrap_err();
+    assert_eq!(error.line, 4);
+    for csv in [
+        "",
+        "Distance,5 km\nOC,800\n50,0.8\n",
+        "Age,5 furlongs\nOC,800\n50,0.8\n",
+        "Age,5 km\n50,0.8\n",
+        "Age,5 km\nOC,fast\n50,0.8\n",
+        "Age,5 km\nOC,800\n",
+        "Age,5 km\nOC,800\n50,0.8,0.9\n",
+        "Age,5 km\nOC,800\n50,80\n",
+        "Age,5 km\nOC,800\nfifty,0.8\n",
+    ] {
+        assert!(male_with(csv).is_err(), "{csv:?} should be rejected");
+    }
+}
@@ -0,0 +1,82 @@
+use parkrust::client::{Cassette, CassetteMode, ParkrunClient};
+use parkrust::models::parkrun::{Event, EventsQuery, Listable, ResultsQuery, RunResult};
+use parkrust::testing::{MockServer, ATHLETE_ID, PASSWORD};
+use parkrust::Error;
+
+/// Nothing listens here, so replayed clients fail if they touch the network
+const UNREACHABLE_URL: &str = "http://127.0.0.1:9/";
+
+fn replay_client(path: &str) -> ParkrunClient {
+    ParkrunClient::builder()
+        .base_url(UNREACHABLE_URL)
+        .cassette(Cassette::replay(path).unwrap())
+        .build()
+        .unwrap()
+}
+
+fn results_query() -> ResultsQuery {
+    ResultsQuery {
+        athlete_id: String::from(ATHLETE_ID),
+    }
+}
+
+#[tokio::test]
+async fn record_then_replay() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("results.json");
+
+    let server = MockServer::start().await;
+    let client = ParkrunClient::builder()
+        .base_url(server.url())
+        .cassette(Cassette::record(&path))
+        .build()
+        .unwrap();
+    let mut client = client.authenticate(ATHLETE_ID, PASSWORD).await.unwrap();
+    let recorded = RunResult::list(results_query(), &mut client).await.unwrap();
+    drop(server);
+
+    let cassette = std::fs::read_to_string(&path).unwrap();
+    assert!(!cassette.contains(&format!("password={PASSWORD}")));
+    assert!(!cassette.contains(&client.token.access_token));
+    assert!(!cassette.contains(&client.token.refresh_token));
+
+    let mut client = replay_client(path.to_str().unwrap())
+        .authenticate(ATHLETE_ID, PASSWORD)
+        .await
+        .unwrap();
+    let replayed = RunResult::list(results_query(), &mut client).await.unwrap();
+    assert_eq!(replayed, recorded);
+}
+
+#[tokio::test]
+async fn replay_missing_request() {
+    let client = replay_client("tests/cassettes/results.json");
+    assert_eq!(
+        client.cassette.as_ref().unwrap().mode(),
+        CassetteMode::Replay
+    );
+    let mut client = client.authenticate(ATHLETE_ID, PASSWORD).await.unwrap();
+
+    let result = Event::list(
+        EventsQuery {
+            athlete_id: String::from(ATHLETE_ID),
+        },
+        &mut client,
+    )
+    .await;
+    assert!(matches!(result, Err(Error::Cassette(_))));
+}
+
+#[tokio::test]
+async fn replay_results_cassette() {
+    let mut client = replay_client("tests/cassettes/results.json")
+        .authenticate(ATHLETE_ID, PASSWORD)
+        .await
+        .unwrap();
+
+    let results = RunResult::list(results_query(), &mut client).await.unwrap();
+
+    assert_eq!(results.len(), 7);
+    assert_eq!(results[6].run_time.to_string(), "01:02:33");
+    assert_eq!(results[0].age_category.to_string(), "SW30-34");
+}
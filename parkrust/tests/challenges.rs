@@ -0,0 +1,188 @@
+use chrono::{Duration, NaiveDate};
+use parkrust::challenges::{
+    alphabet, bingo, compass, country_regionnaire, stopwatch, wilson_index, Challenges,
+    CompassPoint,
+};
+use parkrust::models::parkrun::{Country, Event, EventStatus, RunResult};
+use parkrust::testing::{EventBuilder, RunResultBuilder};
+
+fn saturday(week: i64) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2023, 1, 7).unwrap() + Duration::weeks(week)
+}
+
+fn result(event_number: &str, week: i64) -> RunResult {
+    RunResultBuilder::new()
+        .event_number(event_number)
+        .event_date(saturday(week))
+        .build()
+}
+
+fn timed(run_time: &str) -> RunResult {
+    RunResultBuilder::new().run_time(run_time).build()
+}
+
+fn event(event_number: &str, event_short_name: &str) -> Event {
+    EventBuilder::new()
+        .event_number(event_number)
+        .event_short_name(event_short_name)
+        .build()
+}
+
+#[test]
+fn alphabet_and_compass_use_event_names() {
+    let events = [
+        event("1", "Bushy Park"),
+        event("2", "Northala Fields"),
+        event("3", "Newark"),
+        event("4", "Wimbledon Common"),
+        event("5", "Eastleigh"),
+    ];
+    // Newest first, as the API returns them. Event 9 isn't in the list
+    let results = [
+        result("9", 4),
+        result("4", 3),
+        result("2", 2),
+        result("3", 1),
+        result("1", 0),
+    ];
+
+    let alphabet_progress = alphabet(&results, &events);
+    let compass = compass(&results, &events);
+
+    assert_eq!(alphabet_progress.letters.keys().collect::<String>(), "BNW");
+    // The first event run at for a letter is credited
+    assert_eq!(alphabet_progress.letters[&'N'], "Newark");
+    assert_eq!(alphabet_progress.missing().len(), 23);
+    assert_eq!(
+        compass.points.keys().copied().collect::<Vec<_>>(),
+        [CompassPoint::North]
+    );
+    assert_eq!(compass.points[&CompassPoint::North], "Northala Fields");
+    assert!(!compass.is_complete());
+    assert_eq!(compass.missing().len(), 3);
+    // Results at events that aren't in the list are skipped
+    assert_eq!(alphabet(&results, &events[..1]).count(), 1);
+}
+
+#[test]
+fn alphabet_uses_the_first_letter_without_diacritics() {
+    let results = [result("1", 0), result("2", 1), result("3", 2)];
+    let events = |names: [&str; 3]| {
+        names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| event(&(index + 1).to_string(), name))
+            .collect::<Vec<Event>>()
+    };
+
+    let letters = alphabet(&results, &events(["Ålesund", "Łódź", "Émile Zola"])).letters;
+    assert_eq!(letters.keys().collect::<String>(), "AEL");
+    assert_eq!(letters[&'A'], "Ålesund");
+
+    // Only the first character counts, "5 Bridges" doesn't count for B
+    let letters = alphabet(
+        &results,
+        &events(["5 Bridges", "'s-Hertogenbosch", "øresund"]),
+    )
+    .letters;
+    assert_eq!(letters.keys().collect::<String>(), "O");
+}
+
+#[test]
+fn finish_times_for_bingo_and_stopwatch() {
+    let results = [
+        timed("25:10"),
+        timed("26:12"),
+        timed("28:33"),
+        timed("25:10"),
+        timed("01:02:47"),
+    ];
+
+    let bingo = bingo(&results);
+    let stopwatch = stopwatch(&results);
+
+    assert_eq!(
+        bingo.seconds.iter().copied().collect::<Vec<_>>(),
+        [10, 12, 33, 47]
+    );
+    assert_eq!(bingo.missing().len(), 56);
+    assert!(!bingo.is_complete());
+    assert_eq!(
+        stopwatch.minutes.iter().copied().collect::<Vec<_>>(),
+        [25, 26, 28, 62]
+    );
+    assert_eq!(
+        stopwatch.missing(),
+        [27].into_iter().chain(29..62).collect::<Vec<_>>()
+    );
+    assert!(!stopwatch.is_complete());
+
+    let challenges = Challenges::from_results(&results, &[]);
+    assert_eq!(challenges.bingo, bingo);
+    assert_eq!(challenges.stopwatch, stopwatch);
+    assert_eq!(challenges.alphabet.count(), 0);
+}
+
+#[test]
+fn complete_bingo_and_stopwatch() {
+    let results = (0..60)
+        .map(|second| timed(&format!("{}:{second:02}", 25 + second % 3)))
+        .collect::<Vec<_>>();
+
+    assert!(bingo(&results).is_complete());
+    assert!(stopwatch(&results).is_complete());
+    assert!(!stopwatch(&[]).is_complete());
+}
+
+#[test]
+fn wilson_index_needs_consecutive_run_numbers() {
+    let runs = ["1", "2", "3", "5", "x"]
+        .into_iter()
+        .map(|run_id| RunResultBuilder::new().run_id(run_id).build())
+        .collect::<Vec<_>>();
+
+    assert_eq!(wilson_index(&runs), 3);
+    assert_eq!(wilson_index(&runs[1..]), 0);
+    assert_eq!(wilson_index(&[]), 0);
+}
+
+#[test]
+fn regionnaire_counts_live_events_in_the_country() {
+    let events = [
+        event("1", "Bushy Park"),
+        event("12", "Wimbledon Common"),
+        event("999", "Fulham Palace"),
+        EventBuilder::new()
+            .event_number("998")
+            .event_short_name("Old Deer Park")
+            .event_status(EventStatus::Closed)
+            .build(),
+        // Closed events still count once they've been run
+        EventBuilder::new()
+            .event_number("997")
+            .event_short_name("Roundshaw Downs")
+            .event_status(EventStatus::Closed)
+            .build(),
+        EventBuilder::new()
+            .event_number("996")
+            .event_short_name("Albert Melbourne")
+            .country(Country(3))
+            .build(),
+    ];
+    let results = [
+        result("1", 0),
+        result("12", 1),
+        result("997", 2),
+        result("996", 3),
+    ];
+
+    let regionnaire = country_regionnaire(&results, &events, Country(97));
+
+    assert_eq!(
+        regionnaire.completed,
+        ["Bushy Park", "Wimbledon Common", "Roundshaw Downs"]
+    );
+    assert_eq!(regionnaire.remaining, ["Fulham Palace"]);
+    assert!(!regionnaire.is_complete());
+    assert!(country_regionnaire(&results, &events, Country(3)).is_complete());
+}
@@ -0,0 +1,684 @@
+use chrono::{Duration, NaiveDate, Utc};
+use futures::StreamExt;
+use parkrust::client::requests::{
+    course_records, field_placing, ClubStats, CourseRecords, VolunteerStats,
+};
+use parkrust::client::{parse_response, MemoryTokenStore, ParkrunClient, Token, TokenStore};
+use parkrust::models::parkrun::{
+    Athlete, AuthResponse, Club, Event, EventsQuery, Finisher, Listable, ResultsQuery, Role,
+    RunResult, RunResultsQuery, Sex, VolunteerRole, VolunteersQuery,
+};
+use parkrust::testing::{MockServer, NextLinkStyle, ATHLETE_ID, PASSWORD};
+use parkrust::Error;
+use std::sync::{Arc, Mutex};
+
+fn results_query() -> ResultsQuery {
+    ResultsQuery {
+        athlete_id: String::from(ATHLETE_ID),
+    }
+}
+
+#[tokio::test]
+async fn authenticate_with_valid_credentials() {
+    let server = MockServer::start().await;
+    let client = server
+        .client()
+        .authenticate(ATHLETE_ID, PASSWORD)
+        .await
+        .unwrap();
+
+    assert!(!client.token.access_token.is_empty());
+    assert!(!client.token.refresh_token.is_empty());
+    assert!(!client.token.is_expired());
+}
+
+#[test]
+fn token_from_auth_response_with_invalid_expiry() {
+    let response = AuthResponse {
+        access_token: String::from("access"),
+        refresh_token: String::from("refresh"),
+        expires_in: String::from("soon"),
+    };
+
+    let result = Token::from_auth_response(response);
+
+    assert!(matches!(result, Err(Error::Decode { payload, .. }) if payload == "soon"));
+}
+
+fn response(body: &str) -> reqwest::Response {
+    reqwest::Response::from(http::Response::new(body.to_string()))
+}
+
+#[tokio::test]
+async fn decode_errors_redact_tokens() {
+    // A token response missing `expires_in`
+    let body = r#"{"access_token": "secret-access", "refresh_token": "secret-refresh"}"#;
+
+    let result = parse_response::<AuthResponse>(response(body)).await;
+
+    let Err(Error::Decode { payload, .. }) = result else {
+        panic!("expected a decode error");
+    };
+    assert!(!payload.contains("secret"), "{payload}");
+    assert!(payload.contains("access_token"), "{payload}");
+
+    let result = parse_response::<AuthResponse>(response("access_token=secret-access")).await;
+    assert!(matches!(result, Err(Error::Decode { payload, .. }) if !payload.contains("secret")));
+
+    let result = parse_response::<AuthResponse>(response("<html>Bad gateway</html>")).await;
+    assert!(
+        matches!(result, Err(Error::Decode { payload, .. }) if payload == "<html>Bad gateway</html>")
+    );
+}
+
+#[tokio::test]
+async fn authenticate_with_invalid_credentials() {
+    let server = MockServer::start().await;
+    let result = server.client().authenticate(ATHLETE_ID, "wrong").await;
+
+    assert!(matches!(result, Err(Error::InvalidCredentials)));
+}
+
+#[tokio::test]
+async fn list_follows_next_links() {
+    let server = MockServer::start().await;
+    let mut client = server
+        .client()
+        .authenticate(ATHLETE_ID, PASSWORD)
+        .await
+        .unwrap();
+
+    let results = RunResult::list(results_query(), &mut client).await.unwrap();
+
+    assert_eq!(results.len(), 7);
+    assert_eq!(results[0].run_time.to_string(), "27:41");
+    // Seven results with the default page size of three
+    assert_eq!(server.requests_to("/v1/results").len(), 3);
+}
+
+#[tokio::test]
+async fn list_single_page() {
+    let server = MockServer::start().await;
+    server.set_page_size(100);
+    let mut client = server
+        .client()
+        .authenticate(ATHLETE_ID, PASSWORD)
+        .await
+        .unwrap();
+
+    let results = RunResult::list(results_query(), &mut client).await.unwrap();
+
+    assert_eq!(results.len(), 7);
+    assert_eq!(server.requests_to("/v1/results").len(), 1);
+}
+
+#[tokio::test]
+async fn list_follows_root_relative_and_absolute_links() {
+    for next_link_style in [NextLinkStyle::RootRelative, NextLinkStyle::Absolute] {
+        let server = MockServer::start().await;
+        server.set_next_link_style(next_link_style);
+        let mut client = server
+            .client()
+            .authenticate(ATHLETE_ID, PASSWORD)
+            .await
+            .unwrap();
+
+        // The mock rejects requests with more than one access_token, which absolute links include
+        let results = RunResult::list(results_query(), &mut client).await.unwrap();
+
+        assert_eq!(results.len(), 7, "{next_link_style:?}");
+    }
+}
+
+#[tokio::test]
+async fn list_detects_pagination_loop() {
+    let server = MockServer::start().await;
+    server.set_next_link_style(NextLinkStyle::Loop);
+    let mut client = server
+        .client()
+        .authenticate(ATHLETE_ID, PASSWORD)
+        .await
+        .unwrap();
+
+    let result = RunResult::list(results_query(), &mut client).await;
+
+    assert!(matches!(result, Err(Error::PaginationLoop { .. })));
+    assert_eq!(server.requests_to("/v1/results").len(), 2);
+}
+
+#[tokio::test]
+async fn list_stops_at_max_pages() {
+    let server = MockServer::start().await;
+    let mut client = ParkrunClient::builder()
+        .base_url(server.url())
+        .max_pages(2)
+        .build()
+        .unwrap()
+        .authenticate(ATHLETE_ID, PASSWORD)
+        .await
+        .unwrap();
+
+    let result = RunResult::list(results_query(), &mut client).await;
+
+    assert!(matches!(result, Err(Error::TooManyPages { max_pages: 2 })));
+    assert_eq!(server.requests_to("/v1/results").len(), 2);
+}
+
+#[tokio::test]
+async fn stream_fetches_pages_lazily() {
+    let server = MockServer::start().await;
+    let mut client = server
+        .client()
+        .authenticate(ATHLETE_ID, PASSWORD)
+        .await
+        .unwrap();
+
+    let mut stream = RunResult::stream(results_query(), &mut client);
+    let first = stream.next().await.unwrap().unwrap();
+    assert_eq!(first.run_time.to_string(), "27:41");
+    assert_eq!(server.requests_to("/v1/results").len(), 1);
+
+    let rest = stream.collect::<Vec<_>>().await;
+    assert_eq!(rest.len(), 6);
+    assert!(rest.iter().all(Result::is_ok));
+    assert_eq!(server.requests_to("/v1/results").len(), 3);
+}
+
+#[tokio::test]
+async fn list_page_uses_offset_and_limit() {
+    let server = MockServer::start().await;
+    let mut client = server
+        .client()
+        .authenticate(ATHLETE_ID, PASSWORD)
+        .await
+        .unwrap();
+
+    let page = RunResult::list_page(results_query(), 2, 4, &mut client)
+        .await
+        .unwrap();
+    assert_eq!(page.items.len(), 4);
+    assert_eq!(page.items[0].run_time.to_string(), "28:10");
+    assert!(page.has_next());
+
+    let last_page = RunResult::list_page(results_query(), 6, 4, &mut client)
+        .await
+        .unwrap();
+    assert_eq!(last_page.items.len(), 1);
+    assert!(!last_page.has_next());
+}
+
+#[tokio::test]
+async fn list_events() {
+    let server = MockServer::start().await;
+    let mut client = server
+        .client()
+        .authenticate(ATHLETE_ID, PASSWORD)
+        .await
+        .unwrap();
+
+    let events = Event::list(
+        EventsQuery {
+            athlete_id: String::from(ATHLETE_ID),
+        },
+        &mut client,
+    )
+    .await
+    .unwrap();
+
+    let names = events
+        .iter()
+        .map(|event| event.event_name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["bushy", "wimbledoncommon", "northala"]);
+}
+
+#[tokio::test]
+async fn run_results_list_every_finisher() {
+    let server = MockServer::start().await;
+    let mut client = server
+        .client()
+        .authenticate(ATHLETE_ID, PASSWORD)
+        .await
+        .unwrap();
+    let results = RunResult::list(results_query(), &mut client).await.unwrap();
+
+    let finishers = results[0].finishers(&mut client).await.unwrap();
+
+    assert_eq!(finishers.len(), 6);
+    assert_eq!(server.requests_to("/v1/events/1/runs/512/results").len(), 2);
+    let me = finishers
+        .iter()
+        .find(|finisher| finisher.athlete_id == ATHLETE_ID)
+        .unwrap();
+    assert_eq!(me.finish_position, 4);
+    assert_eq!(me.full_name(), "Paula Runner");
+    assert_eq!(
+        finishers[0].club_name.as_deref(),
+        Some("Bushy Park Runners")
+    );
+    assert!(finishers[5].is_unknown());
+    assert_eq!(finishers[5].age_category, None);
+
+    let query = server.requests_to("/v1/events/1/runs/512/results")[0]
+        .query
+        .clone();
+    assert!(!query.contains_key("eventNumber"));
+    assert!(!query.contains_key("runId"));
+}
+
+#[tokio::test]
+async fn list_volunteer_roles_with_stats() {
+    let server = MockServer::start().await;
+    let mut client = server
+        .client()
+        .authenticate(ATHLETE_ID, PASSWORD)
+        .await
+        .unwrap();
+
+    let roles = VolunteerRole::list(
+        VolunteersQuery {
+            athlete_id: String::from(ATHLETE_ID),
+        },
+        &mut client,
+    )
+    .await
+    .unwrap();
+    let stats = VolunteerStats::from_roles(&roles);
+
+    assert_eq!(roles.len(), 6);
+    assert_eq!(roles[0].role, Role::BarcodeScanning);
+    assert_eq!(stats.total_roles, 6);
+    // Barcode scanning and finish tokens at the same run are one credit
+    assert_eq!(stats.total_credits, 5);
+    assert_eq!(stats.most_common_role(), Some(&Role::Marshal));
+    assert_eq!(stats.roles_by_count[0], (Role::Marshal, 2));
+    assert_eq!(stats.event_count(), 3);
+}
+
+#[tokio::test]
+async fn get_me_and_athlete_by_id() {
+    let server = MockServer::start().await;
+    let mut client = server
+        .client()
+        .authenticate(ATHLETE_ID, PASSWORD)
+        .await
+        .unwrap();
+
+    let me = client.get_me().await.unwrap();
+    assert_eq!(me.athlete_id, ATHLETE_ID);
+    assert_eq!(me.full_name(), "Paula Runner");
+    assert_eq!(me.sex, Some(Sex::Female));
+    assert_eq!(me.home_run_name.as_deref(), Some("Bushy Park"));
+    assert_eq!((me.run_count, me.volunteer_count), (Some(7), Some(2)));
+    assert_eq!(server.requests_to("/v1/me").len(), 1);
+
+    let athlete = Athlete::get("654321", &mut client).await.unwrap();
+    assert_eq!(athlete.full_name(), "Alex Quick");
+    assert_eq!(athlete.run_count, Some(143));
+
+    // The printed barcode's `A` prefix is stripped
+    let athlete = Athlete::get("A333333", &mut client).await.unwrap();
+    assert_eq!(athlete.full_name(), "Chris Pace");
+    assert_eq!(server.requests_to("/v1/athletes/333333").len(), 1);
+
+    assert!(matches!(
+        Athlete::get("999999", &mut client).await,
+        Err(Error::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn get_club_and_members() {
+    let server = MockServer::start().await;
+    let mut client = server
+        .client()
+        .authenticate(ATHLETE_ID, PASSWORD)
+        .await
+        .unwrap();
+
+    let club = Club::get("1001", &mut client).await.unwrap();
+    let members = Club::members("1001", &mut client).await.unwrap();
+
+    assert_eq!(club.club_name, "Bushy Park Runners");
+    assert_eq!(club.member_count, Some(2));
+    let names = members
+        .iter()
+        .map(|athlete| athlete.full_name())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["Paula Runner", "Alex Quick"]);
+    assert!(!server.requests_to("/v1/clubs/1001/members")[0]
+        .query
+        .contains_key("clubId"));
+    assert!(matches!(
+        Club::get("9999", &mut client).await,
+        Err(Error::NotFound(_))
+    ));
+
+    // Ids are a single path segment, so can't reach another endpoint
+    let result = Club::get("1001/members", &mut client).await;
+    assert!(matches!(result, Err(Error::NotFound(_))));
+    assert_eq!(server.requests_to("/v1/clubs/1001%2Fmembers").len(), 1);
+    Club::members("1001/../1002", &mut client).await.unwrap();
+    assert_eq!(
+        server
+            .requests_to("/v1/clubs/1001%2F..%2F1002/members")
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn club_stats_cover_every_member() {
+    let server = MockServer::start().await;
+    let mut client = server
+        .client()
+        .authenticate(ATHLETE_ID, PASSWORD)
+        .await
+        .unwrap();
+    let date = |month, day| NaiveDate::from_ymd_opt(2023, month, day).unwrap();
+
+    let members = Club::member_results("1001", &mut client).await.unwrap();
+    let stats = ClubStats::from_members(&members, date(1, 1), date(4, 15));
+
+    assert_eq!(stats.total_members, 2);
+    // Alex's run on the 31st of December is in the previous season
+    assert_eq!(stats.season_runs(), 10);
+    assert_eq!(stats.season.fastest_time.unwrap().to_string(), "16:55");
+    assert_eq!(stats.pbs_this_month.len(), 2);
+    assert!(stats
+        .pbs_this_month
+        .iter()
+        .all(|pb| pb.result.event_date == date(4, 1)));
+}
+
+#[tokio::test]
+async fn expired_token_is_refreshed_before_request() {
+    let server = MockServer::start().await;
+    let mut client = server
+        .client()
+        .authenticate(ATHLETE_ID, PASSWORD)
+        .await
+        .unwrap();
+    let old_token = client.token.access_token.clone();
+    client.token.expires_at = Utc::now() - Duration::seconds(1);
+
+    RunResult::list(results_query(), &mut client).await.unwrap();
+
+    assert_ne!(client.token.access_token, old_token);
+    assert!(!client.token.is_expired());
+    assert_eq!(server.requests_to("/auth/refresh").len(), 1);
+}
+
+#[tokio::test]
+async fn rejected_token_is_refreshed_and_retried() {
+    let server = MockServer::start().await;
+    let store = Arc::new(MemoryTokenStore::new());
+    let refreshed = Arc::new(Mutex::new(Vec::<Token>::new()));
+    let callback_refreshed = refreshed.clone();
+    let mut client = server
+        .client()
+        .authenticate(ATHLETE_ID, PASSWORD)
+        .await
+        .unwrap()
+        .with_token_store(store.clone())
+        .unwrap()
+        .on_token_refresh(move |token| callback_refreshed.lock().unwrap().push(token.clone()));
+    server.revoke_access_tokens();
+
+    let me = client.get_me().await.unwrap();
+
+    assert_eq!(me.athlete_id, ATHLETE_ID);
+    assert_eq!(server.requests_to("/v1/me").len(), 2);
+    assert_eq!(store.load().unwrap(), Some(client.token.clone()));
+    assert_eq!(*refreshed.lock().unwrap(), [client.token.clone()]);
+}
+
+#[tokio::test]
+async fn refresh_with_revoked_refresh_token() {
+    let server = MockServer::start().await;
+    let mut client = server
+        .client()
+        .authenticate(ATHLETE_ID, PASSWORD)
+        .await
+        .unwrap();
+    server.revoke_refresh_tokens();
+
+    assert!(matches!(
+        client.refresh_token().await,
+        Err(Error::InvalidRefreshToken)
+    ));
+}
+
+#[tokio::test]
+async fn rejected_token_with_revoked_refresh_token() {
+    let server = MockServer::start().await;
+    let mut client = server
+        .client()
+        .authenticate(ATHLETE_ID, PASSWORD)
+        .await
+        .unwrap();
+    server.revoke_access_tokens();
+    server.revoke_refresh_tokens();
+
+    assert!(matches!(
+        client.get_me().await,
+        Err(Error::InvalidRefreshToken)
+    ));
+}
+
+#[tokio::test]
+async fn base_url_path_is_kept() {
+    let server = MockServer::start().await;
+    let mut client = server
+        .client()
+        .authenticate(ATHLETE_ID, PASSWORD)
+        .await
+        .unwrap();
+    let expected = RunResult::list(results_query(), &mut client).await.unwrap();
+
+    for next_link_style in [
+        NextLinkStyle::DotRelative,
+        NextLinkStyle::RootRelative,
+        NextLinkStyle::Absolute,
+    ] {
+        let server = MockServer::start_at("/parkrun/").await;
+        server.set_next_link_style(next_link_style);
+        for base_url in [server.url(), server.url().trim_end_matches('/').to_string()] {
+            let mut client = ParkrunClient::builder()
+                .base_url(base_url)
+                .build()
+                .unwrap()
+                .authenticate(ATHLETE_ID, PASSWORD)
+                .await
+                .unwrap();
+
+            let results = RunResult::list(results_query(), &mut client).await.unwrap();
+
+            assert_eq!(results, expected, "{next_link_style:?}");
+        }
+        assert_eq!(server.requests_to("/user_auth.php").len(), 2);
+    }
+}
+
+#[tokio::test]
+async fn builder_sets_user_agent_and_credentials() {
+    let server = MockServer::start().await;
+    let client = ParkrunClient::builder()
+        .base_url(server.url())
+        .user_agent("my-app/1.0")
+        .credentials("my-app", "my-secret")
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .unwrap();
+
+    client.authenticate(ATHLETE_ID, PASSWORD).await.unwrap();
+
+    let request = &server.requests_to("/user_auth.php")[0];
+    assert_eq!(request.headers["user-agent"], "my-app/1.0");
+    assert_eq!(
+        request.headers["authorization"],
+        "Basic bXktYXBwOm15LXNlY3JldA=="
+    );
+}
+
+#[tokio::test]
+async fn builder_uses_request_client() {
+    let server = MockServer::start().await;
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("x-test", "custom client".parse().unwrap());
+    let client = ParkrunClient::builder()
+        .base_url(server.url())
+        .request_client(
+            reqwest::Client::builder()
+                .default_headers(headers)
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap();
+
+    client.authenticate(ATHLETE_ID, PASSWORD).await.unwrap();
+
+    let request = &server.requests_to("/user_auth.php")[0];
+    assert_eq!(request.headers["x-test"], "custom client");
+    assert!(!request.headers.contains_key("user-agent"));
+}
+
+#[test]
+fn builder_rejects_timeout_or_proxy_with_request_client() {
+    let result = ParkrunClient::builder()
+        .request_client(reqwest::Client::new())
+        .timeout(std::time::Duration::from_secs(10))
+        .build();
+    assert!(matches!(result, Err(Error::InvalidConfig(_))));
+
+    let result = ParkrunClient::builder()
+        .request_client(reqwest::Client::new())
+        .proxy("http://localhost:3128")
+        .build();
+    assert!(matches!(result, Err(Error::InvalidConfig(_))));
+}
+
+#[tokio::test]
+async fn builder_sends_requests_through_proxy() {
+    let server = MockServer::start().await;
+    // Nothing is listening here, requests only succeed if they go through the proxy
+    let client = ParkrunClient::builder()
+        .base_url("http://parkrun.invalid/")
+        .proxy(server.url())
+        .build()
+        .unwrap();
+
+    client.authenticate(ATHLETE_ID, PASSWORD).await.unwrap();
+
+    assert_eq!(server.requests_to("/user_auth.php").len(), 1);
+}
+
+#[test]
+fn builder_rejects_invalid_urls() {
+    assert!(matches!(
+        ParkrunClient::builder().base_url("not a url").build(),
+        Err(Error::InvalidUrl(_))
+    ));
+    assert!(ParkrunClient::builder().proxy("not a url").build().is_err());
+}
+
+#[tokio::test]
+async fn place_against_the_field_and_course_records() {
+    let server = MockServer::start().await;
+    let mut client = server
+        .client()
+        .authenticate(ATHLETE_ID, PASSWORD)
+        .await
+        .unwrap();
+    let results = RunResult::list(results_query(), &mut client).await.unwrap();
+    let result = results
+        .iter()
+        .find(|result| result.run_id == "512")
+        .unwrap();
+
+    let finishers = result.finishers(&mut client).await.unwrap();
+    let placing = field_placing(result, &finishers).unwrap();
+
+    assert_eq!((placing.position, placing.field_size), (4, 6));
+    assert_eq!(placing.percentile(), Some(2.0 / 6.0 * 100.0));
+    assert_eq!(
+        (placing.gender_position, placing.gender_field_size),
+        (Some(2), 2)
+    );
+    assert_eq!(
+        (placing.category_position, placing.category_field_size),
+        (Some(2), 2)
+    );
+    assert_eq!(placing.ahead.unwrap().athlete_id, "333333");
+    assert_eq!(placing.behind.unwrap().athlete_id, "444444");
+    let stranger = RunResult {
+        athlete_id: String::from("999999"),
+        ..result.clone()
+    };
+    assert_eq!(field_placing(&stranger, &finishers), None);
+
+    let events = Event::list(
+        EventsQuery {
+            athlete_id: String::from(ATHLETE_ID),
+        },
+        &mut client,
+    )
+    .await
+    .unwrap();
+    let event = events
+        .iter()
+        .find(|event| event.event_number == "1")
+        .unwrap();
+    let finishers = event
+        .run_finishers(["512", "521"], &mut client)
+        .await
+        .unwrap();
+    let records = course_records(&finishers);
+
+    assert_eq!(finishers.len(), 9);
+    let male = records.for_sex(Sex::Male).unwrap();
+    assert_eq!(
+        (male.athlete_id.as_str(), male.run_id.as_str()),
+        ("654321", "512")
+    );
+    assert_eq!(male.run_time.to_string(), "17:02");
+    let female = records.female.unwrap();
+    assert_eq!(
+        (female.full_name().as_str(), female.run_id.as_str()),
+        ("Sam Swift", "521")
+    );
+    assert_eq!(course_records(&[]), CourseRecords::default());
+}
+
+#[tokio::test]
+async fn endpoint_placeholders_are_encoded() {
+    let server = MockServer::start().await;
+    let mut client = server
+        .client()
+        .authenticate(ATHLETE_ID, PASSWORD)
+        .await
+        .unwrap();
+
+    let finishers = Finisher::list(
+        RunResultsQuery {
+            event_number: String::from("1/runs/512"),
+            run_id: String::from("512?limit=1"),
+        },
+        &mut client,
+    )
+    .await
+    .unwrap();
+
+    assert!(finishers.is_empty());
+    let paths = server
+        .requests()
+        .into_iter()
+        .filter(|request| request.path.starts_with("/v1/events/"))
+        .map(|request| request.path)
+        .collect::<Vec<String>>();
+    assert_eq!(
+        paths,
+        ["/v1/events/1%2Fruns%2F512/runs/512%3Flimit%3D1/results"]
+    );
+}
@@ -0,0 +1,213 @@
+use chrono::{Duration, NaiveDate};
+use parkrust::comparison::{
+    age_grade_trends, head_to_head, head_to_heads, pb_history, pb_race, shared_events, Trend,
+    TrendPoint,
+};
+use parkrust::models::parkrun::{Athlete, AthleteResults, Series};
+use parkrust::testing::RunResultBuilder;
+use serde_json::json;
+
+fn saturday(week: i64) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2023, 1, 7).unwrap() + Duration::weeks(week)
+}
+
+fn run(event_number: &str, run_id: &str, week: i64) -> RunResultBuilder {
+    RunResultBuilder::new()
+        .event_number(event_number)
+        .run_id(run_id)
+        .event_date(saturday(week))
+}
+
+fn athlete(athlete_id: &str, results: Vec<RunResultBuilder>) -> AthleteResults {
+    let athlete: Athlete = serde_json::from_value(json!({
+        "AthleteID": athlete_id,
+        "FirstName": "Test",
+        "LastName": athlete_id,
+    }))
+    .unwrap();
+    AthleteResults {
+        athlete,
+        results: results
+            .into_iter()
+            .map(|result| result.athlete_id(athlete_id).build())
+            .collect(),
+    }
+}
+
+#[test]
+fn head_to_head_matches_shared_runs() {
+    // Newest first, as the API returns them
+    let paula = athlete(
+        "1",
+        vec![
+            run("1", "503", 3).finish_position(25).age_grading(63.0),
+            // The same run id at another event isn't the same run
+            run("2", "501", 2).finish_position(20).age_grading(60.0),
+            run("1", "501", 1).finish_position(30).age_grading(62.0),
+            run("1", "500", 0).finish_position(40).age_grading(58.0),
+        ],
+    );
+    let alex = athlete(
+        "2",
+        vec![
+            run("1", "503", 3).finish_position(10).age_grading(65.0),
+            run("3", "501", 2).finish_position(5).age_grading(70.0),
+            run("1", "501", 1).finish_position(35).age_grading(58.0),
+        ],
+    );
+
+    let record = head_to_head(&paula, &alex);
+
+    let runs = record
+        .encounters
+        .iter()
+        .map(|encounter| (encounter.event_number.as_str(), encounter.run_id.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(runs, [("1", "501"), ("1", "503")]);
+    assert_eq!(
+        (record.athlete_id.as_str(), record.other_athlete_id.as_str()),
+        ("1", "2")
+    );
+    assert_eq!((record.wins(), record.losses()), (1, 1));
+    assert_eq!(record.average_age_grade_difference(), Some(1.0));
+    assert!(record.age_grade_trend().slope_per_year().unwrap() < 0.0);
+
+    let reversed = record.reversed();
+    assert_eq!(reversed.athlete_id, "2");
+    assert_eq!(reversed.encounters[0].position, 35);
+    assert_eq!(reversed.average_age_grade_difference(), Some(-1.0));
+    assert_eq!(reversed, head_to_head(&alex, &paula));
+
+    let chris = athlete("3", vec![run("1", "500", 0).finish_position(12)]);
+    let records = head_to_heads(&[paula, alex, chris]);
+    let pairs = records
+        .iter()
+        .map(|record| (record.athlete_id.as_str(), record.other_athlete_id.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(pairs, [("1", "2"), ("1", "3"), ("2", "3")]);
+    assert_eq!(records[0], record);
+    assert_eq!(records[1].wins(), 0);
+    assert!(records[2].encounters.is_empty());
+    assert_eq!(records[2].average_age_grade_difference(), None);
+}
+
+#[test]
+fn shared_events_across_athletes() {
+    let paula = athlete("1", vec![run("1", "500", 0), run("2", "300", 1)]);
+    let alex = athlete("2", vec![run("3", "100", 0), run("1", "501", 1)]);
+    let chris = athlete("3", vec![run("2", "301", 2), run("1", "502", 2)]);
+
+    let shared = shared_events(&[paula.clone(), alex, chris.clone()]);
+
+    assert_eq!(shared.into_iter().collect::<Vec<_>>(), ["1"]);
+    let mut shared = shared_events(&[paula, chris])
+        .into_iter()
+        .collect::<Vec<_>>();
+    shared.sort();
+    assert_eq!(shared, ["1", "2"]);
+    assert!(shared_events(&[]).is_empty());
+}
+
+#[test]
+fn pb_race_tracks_each_pb() {
+    let paula = athlete(
+        "1",
+        vec![
+            run("1", "504", 4).run_time("25:10"),
+            run("1", "503", 3).run_time("25:40"),
+            run("1", "502", 2).run_time("26:30"),
+            run("1", "501", 1).run_time("26:00"),
+            run("9", "20", 0).series(Series::Junior).run_time("10:00"),
+        ],
+    );
+    let alex = athlete("2", vec![run("1", "503", 3).run_time("17:02")]);
+
+    let athletes = [paula, alex];
+
+    let races = pb_race(&athletes, Series::FiveK);
+
+    let pbs = races[0]
+        .points
+        .iter()
+        .map(|point| (point.date, point.run_time.to_string()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        pbs,
+        [
+            (saturday(1), String::from("26:00")),
+            (saturday(3), String::from("25:40")),
+            (saturday(4), String::from("25:10")),
+        ]
+    );
+    assert_eq!(races[0].pb_on(saturday(0)), None);
+    assert_eq!(races[0].pb_on(saturday(2)).unwrap().to_string(), "26:00");
+    assert_eq!(races[0].pb_on(saturday(10)).unwrap().to_string(), "25:10");
+    assert_eq!(races[1].athlete_id, "2");
+    assert_eq!(races[1].pb_on(saturday(3)).unwrap().to_string(), "17:02");
+    assert_eq!(pb_history(&athletes[0].results, Series::Junior).len(), 1);
+    assert!(pb_history(&athletes[1].results, Series::Junior).is_empty());
+}
+
+#[test]
+fn trend_slope_per_year() {
+    let trend = |points: &[(i64, f32)]| Trend {
+        athlete_id: String::from("1"),
+        points: points
+            .iter()
+            .map(|(week, value)| TrendPoint {
+                date: saturday(*week),
+                value: *value,
+            })
+            .collect(),
+    };
+    let weeks_per_year = 365.25 / 7.0;
+
+    // A steady rise of one point a week
+    let slope = trend(&[(0, 50.0), (1, 51.0), (2, 52.0)])
+        .slope_per_year()
+        .unwrap();
+    assert!((slope - weeks_per_year).abs() < 0.001, "{slope}");
+
+    // A least squares fit through noisy points
+    let slope = trend(&[(0, 50.0), (1, 53.0), (2, 52.0), (3, 55.0)])
+        .slope_per_year()
+        .unwrap();
+    assert!((slope - 1.4 * weeks_per_year).abs() < 0.001, "{slope}");
+
+    assert_eq!(trend(&[(0, 60.0), (1, 60.0)]).slope_per_year(), Some(0.0));
+    assert_eq!(trend(&[(0, 60.0)]).slope_per_year(), None);
+    assert_eq!(trend(&[(2, 60.0), (2, 62.0)]).slope_per_year(), None);
+    assert_eq!(trend(&[]).slope_per_year(), None);
+}
+
+#[test]
+fn age_grade_trends_per_athlete() {
+    let paula = athlete(
+        "1",
+        vec![
+            run("1", "502", 2).age_grading(62.0),
+            run("1", "500", 0).age_grading(60.0),
+            run("1", "501", 1).age_grading(61.0),
+        ],
+    );
+    let alex = athlete(
+        "2",
+        vec![
+            run("1", "502", 2).age_grading(70.0),
+            run("1", "501", 1).age_grading(70.0),
+        ],
+    );
+
+    let trends = age_grade_trends(&[paula, alex]);
+
+    assert_eq!(trends[0].athlete_id, "1");
+    let values = trends[0]
+        .points
+        .iter()
+        .map(|point| point.value)
+        .collect::<Vec<_>>();
+    assert_eq!(values, [60.0, 61.0, 62.0]);
+    assert!(trends[0].slope_per_year().unwrap() > 0.0);
+    assert_eq!(trends[1].points[0].date, saturday(1));
+    assert_eq!(trends[1].slope_per_year(), Some(0.0));
+}
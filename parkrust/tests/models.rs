@@ -0,0 +1,319 @@
+use chrono::NaiveDate;
+use parkrust::models::parkrun::{
+    Athlete, Coordinates, Country, Event, EventStatus, MilestoneProgress, Role, RunResult, RunTime,
+    JUNIOR_RUN_MILESTONE, RUN_MILESTONES, VOLUNTEER_MILESTONES,
+};
+use parkrust::testing::RunResultBuilder;
+use parkrust_derive::parkrun_model;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+fn result_json() -> serde_json::Value {
+    RunResultBuilder::new()
+        .run_id("812")
+        .finish_position(102)
+        .gender_position(21)
+        .run_time("27:41")
+        .age_grading(53.52)
+        .age_category("SW30-34")
+        .first_timer(true)
+        .field("Assisted", serde_json::Value::Null)
+        .json()
+}
+
+#[test]
+fn field_names_follow_api_conventions() {
+    let result: RunResult = serde_json::from_value(result_json()).unwrap();
+    assert_eq!(result.run_id, "812");
+    assert_eq!(result.athlete_id, "123456");
+
+    let serialized = serde_json::to_value(&result).unwrap();
+    let object = serialized.as_object().unwrap();
+    for key in ["SeriesID", "RunId", "AthleteID", "WasPbRun", "GenuinePB"] {
+        assert!(object.contains_key(key), "missing {key}");
+    }
+}
+
+#[test]
+fn missing_or_null_flags_are_false() {
+    let mut json = result_json();
+    let object = json.as_object_mut().unwrap();
+    object.remove("WasPbRun");
+    object.insert(String::from("GenuinePB"), json!(null));
+
+    let result: RunResult = serde_json::from_value(json).unwrap();
+    assert!(!result.was_pb_run);
+    assert!(!result.genuine_pb);
+    assert!(result.first_timer);
+}
+
+#[test]
+fn malformed_records_fail_to_decode() {
+    for (key, value) in [
+        ("FinishPosition", json!("x")),
+        ("RunTime", json!("abc")),
+        ("EventDate", json!("07/01/2023")),
+        ("WasPbRun", json!("2")),
+    ] {
+        let mut json = result_json();
+        json[key] = value;
+        assert!(
+            serde_json::from_value::<RunResult>(json).is_err(),
+            "{key} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn optional_athlete_fields_may_be_missing() {
+    let athlete: Athlete = serde_json::from_value(json!({
+        "AthleteID": "123456",
+        "FirstName": "Paula",
+        "LastName": "Runner",
+    }))
+    .unwrap();
+    assert_eq!(athlete.home_run_id, None);
+    assert_eq!(athlete.run_count, None);
+}
+
+fn athlete(age_category: &str, run_count: u32, volunteer_count: u32) -> Athlete {
+    serde_json::from_value(json!({
+        "AthleteID": "123456",
+        "FirstName": "Paula",
+        "LastName": "Runner",
+        "AgeCategory": age_category,
+        "RunCount": run_count.to_string(),
+        "VolunteerCount": volunteer_count.to_string(),
+    }))
+    .unwrap()
+}
+
+fn progress(milestone: u32, remaining: u32) -> Option<MilestoneProgress> {
+    Some(MilestoneProgress {
+        milestone,
+        remaining,
+    })
+}
+
+#[test]
+fn run_milestones() {
+    let runner = athlete("SW30-34", 0, 0);
+    assert_eq!(runner.clothing_milestone(), None);
+    assert_eq!(runner.next_run_milestone(), progress(25, 25));
+
+    let runner = athlete("SW30-34", 50, 0);
+    assert_eq!(runner.clothing_milestone(), Some(50));
+    assert_eq!(runner.next_run_milestone(), progress(100, 50));
+
+    let runner = athlete("SW30-34", 99, 0);
+    assert_eq!(runner.clothing_milestone(), Some(50));
+    assert_eq!(runner.next_run_milestone(), progress(100, 1));
+
+    let runner = athlete("SW30-34", 100, 0);
+    assert_eq!(runner.clothing_milestone(), Some(100));
+    assert_eq!(runner.next_run_milestone(), progress(250, 150));
+
+    let last_milestone = RUN_MILESTONES[RUN_MILESTONES.len() - 1];
+    let runner = athlete("VW70-74", last_milestone + 12, 0);
+    assert_eq!(runner.clothing_milestone(), Some(last_milestone));
+    assert_eq!(runner.next_run_milestone(), None);
+}
+
+#[test]
+fn junior_run_milestone() {
+    let junior = athlete("JW10", JUNIOR_RUN_MILESTONE - 1, 0);
+    assert_eq!(junior.clothing_milestone(), None);
+    assert_eq!(
+        junior.next_run_milestone(),
+        progress(JUNIOR_RUN_MILESTONE, 1)
+    );
+
+    let junior = athlete("JW11-14", JUNIOR_RUN_MILESTONE, 0);
+    assert_eq!(junior.clothing_milestone(), Some(JUNIOR_RUN_MILESTONE));
+    assert_eq!(junior.next_run_milestone(), progress(25, 15));
+
+    // Only juniors have the 10 milestone
+    let senior = athlete("SW18-19", JUNIOR_RUN_MILESTONE, 0);
+    assert_eq!(senior.clothing_milestone(), None);
+    assert_eq!(senior.next_run_milestone(), progress(25, 15));
+}
+
+#[test]
+fn volunteer_milestones() {
+    assert_eq!(
+        athlete("SW30-34", 0, 0).next_volunteer_milestone(),
+        progress(25, 25)
+    );
+    assert_eq!(
+        athlete("SW30-34", 0, 25).next_volunteer_milestone(),
+        progress(50, 25)
+    );
+    assert_eq!(
+        athlete("SW30-34", 0, 99).next_volunteer_milestone(),
+        progress(100, 1)
+    );
+    let last_milestone = VOLUNTEER_MILESTONES[VOLUNTEER_MILESTONES.len() - 1];
+    assert_eq!(
+        athlete("SW30-34", 0, last_milestone).next_volunteer_milestone(),
+        None
+    );
+}
+
+#[test]
+fn milestones_need_counts() {
+    let athlete: Athlete = serde_json::from_value(json!({
+        "AthleteID": "123456",
+        "FirstName": "Paula",
+        "LastName": "Runner",
+    }))
+    .unwrap();
+    assert_eq!(athlete.clothing_milestone(), None);
+    assert_eq!(athlete.next_run_milestone(), None);
+    assert_eq!(athlete.next_volunteer_milestone(), None);
+}
+
+#[test]
+fn event_fields_are_typed() {
+    let event: Event = serde_json::from_value(json!({
+        "EventNumber": "1",
+        "EventName": "bushy",
+        "EventShortName": "Bushy Park",
+        "EventLongName": "Bushy parkrun",
+        "EventLocation": "Bushy Park, Teddington",
+        "CountryCode": "97",
+        "PreferredLanguage": "en-gb",
+        "SeriesID": "1",
+        "NextAnniversary": "2024-10-05",
+        "HomeRunSelection": "1",
+        "StatusLive": "1",
+        "AnniversarySaturdayOfMonth": "1",
+        "EventStatus": "Live",
+        "UserFavourite": null,
+        "AccessibleToPublic": "0",
+        "Latitude": "51.410992",
+        "Longitude": "-0.335791"
+    }))
+    .unwrap();
+
+    assert_eq!(event.country, Country(97));
+    assert_eq!(event.country.to_string(), "United Kingdom");
+    assert_eq!(event.next_anniversary, NaiveDate::from_ymd_opt(2024, 10, 5));
+    assert_eq!(event.event_status, EventStatus::Live);
+    assert!(event.is_live());
+    assert!(!event.user_favourite);
+    assert!(!event.accessible_to_public);
+
+    let wimbledon = Coordinates::new(51.4337, -0.2347);
+    let distance = event.distance_km(&wimbledon).unwrap();
+    assert!((7.0..8.0).contains(&distance), "{distance}");
+
+    let serialized = serde_json::to_value(&event).unwrap();
+    assert_eq!(serialized["CountryCode"], "97");
+    assert_eq!(serialized["EventStatus"], "Live");
+    assert_eq!(serialized["StatusLive"], "1");
+}
+
+#[test]
+fn unknown_event_status_and_country() {
+    assert_eq!(
+        "Paused".parse::<EventStatus>().unwrap(),
+        EventStatus::Other(String::from("Paused"))
+    );
+    assert_eq!(Country(999).name(), None);
+    assert_eq!(Country(999).to_string(), "Country 999");
+}
+
+#[parkrun_model(lenient)]
+struct LenientModel {
+    pub event_id: String,
+    pub finishers: u32,
+}
+
+#[parkrun_model()]
+struct PartlyDefaultedModel {
+    pub event_id: String,
+    #[parkrun(default)]
+    pub finishers: u32,
+    #[parkrun(field = "EventURL2")]
+    pub url: String,
+}
+
+#[test]
+fn lenient_models_default_missing_fields() {
+    let model: LenientModel = serde_json::from_value(json!({ "Unknown": "field" })).unwrap();
+    assert_eq!(model.event_id, "");
+    assert_eq!(model.finishers, 0);
+}
+
+#[test]
+fn field_overrides_and_defaults() {
+    let model: PartlyDefaultedModel =
+        serde_json::from_value(json!({ "EventID": "1", "EventURL2": "bushy" })).unwrap();
+    assert_eq!(model.event_id, "1");
+    assert_eq!(model.finishers, 0);
+    assert_eq!(model.url, "bushy");
+
+    assert!(serde_json::from_value::<PartlyDefaultedModel>(json!({ "EventID": "1" })).is_err());
+}
+
+#[test]
+fn volunteer_roles_parse_loosely() {
+    assert_eq!("Run Director".parse(), Ok(Role::RunDirector));
+    assert_eq!("run director".parse(), Ok(Role::RunDirector));
+    assert_eq!(
+        "Volunteer Coordinator".parse(),
+        Ok(Role::VolunteerCoordinator)
+    );
+    assert_eq!("Post-event Close".parse(), Ok(Role::PostEventClose));
+    assert_eq!(
+        "Cake Baker".parse(),
+        Ok(Role::Other(String::from("Cake Baker")))
+    );
+
+    let role: Role = serde_json::from_value(json!("Timekeeper")).unwrap();
+    assert_eq!(role, Role::Timekeeper);
+    assert_eq!(
+        serde_json::to_value(Role::FirstTimersBriefing).unwrap(),
+        json!("First Timers Briefing")
+    );
+}
+
+#[test]
+fn run_times_parse_minutes_and_hours() {
+    let time: RunTime = "27:41".parse().unwrap();
+    assert_eq!(time.num_seconds(), 27 * 60 + 41);
+    assert_eq!(time.to_string(), "27:41");
+
+    let time: RunTime = "01:02:03".parse().unwrap();
+    assert_eq!((time.hours(), time.minutes(), time.seconds()), (1, 2, 3));
+    assert_eq!(time.to_string(), "01:02:03");
+
+    let time: RunTime = "00:19:05".parse().unwrap();
+    assert_eq!(time.to_string(), "19:05");
+    assert_eq!("75:00".parse::<RunTime>().unwrap().to_string(), "01:15:00");
+}
+
+#[test]
+fn invalid_run_times_are_rejected() {
+    for value in [
+        "", "abc", "-1:00", "27:60", "1:60:00", "1:2:3:4", "27", "27:4a", "+1:00",
+    ] {
+        assert!(
+            value.parse::<RunTime>().is_err(),
+            "{value:?} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn run_times_serialize_in_the_api_format() {
+    let time: RunTime = serde_json::from_value(json!("00:27:41")).unwrap();
+    assert_eq!(time, RunTime::from_seconds(27 * 60 + 41));
+    assert_eq!(serde_json::to_value(time).unwrap(), json!("00:27:41"));
+    assert_eq!(
+        serde_json::to_value(RunTime::from_seconds(3723)).unwrap(),
+        json!("01:02:03")
+    );
+    assert!(serde_json::from_value::<RunTime>(json!("27:61")).is_err());
+    assert!(serde_json::from_value::<RunTime>(json!(1661)).is_err());
+}
@@ -0,0 +1,79 @@
+use chrono::{Duration, NaiveDate};
+use parkrust::models::parkrun::{RunResult, Series};
+use parkrust::progression::{
+    current_pb, event_pb_ladders, milestones, pb_ladder, time_since_last_pb, Milestone,
+};
+use parkrust::testing::RunResultBuilder;
+
+fn result(series: Series, event_number: &str, date: NaiveDate, run_time: &str) -> RunResult {
+    RunResultBuilder::new()
+        .series(series)
+        .event_number(event_number)
+        .event_date(date)
+        .run_time(run_time)
+        .build()
+}
+
+fn saturday(week: i64) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2023, 1, 7).unwrap() + Duration::weeks(week)
+}
+
+#[test]
+fn pb_ladder_is_chronological() {
+    // Newest first, as the API returns them
+    let results = [
+        result(Series::FiveK, "2", saturday(3), "00:25:10"),
+        result(Series::Junior, "9", saturday(2), "00:09:30"),
+        result(Series::FiveK, "1", saturday(1), "00:26:30"),
+        result(Series::FiveK, "1", saturday(0), "00:26:00"),
+    ];
+
+    let ladder = pb_ladder(&results, Series::FiveK);
+
+    let times = ladder
+        .iter()
+        .map(|pb| pb.run_time.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(times, ["26:00", "25:10"]);
+    assert_eq!(ladder[0].improvement, None);
+    assert_eq!(ladder[1].improvement.unwrap().to_string(), "00:50");
+    assert_eq!(pb_ladder(&results, Series::Junior).len(), 1);
+    assert_eq!(current_pb(&results, Series::FiveK), ladder.last().cloned());
+    assert_eq!(
+        time_since_last_pb(&results, Series::FiveK, saturday(5)),
+        Some(Duration::weeks(2))
+    );
+
+    let event_ladders = event_pb_ladders(&results);
+    assert_eq!(event_ladders.len(), 3);
+    assert_eq!(event_ladders["1"].len(), 1);
+    assert_eq!(event_ladders["1"][0].run_time.to_string(), "26:00");
+}
+
+#[test]
+fn milestones_in_order() {
+    let results = (0..55)
+        .map(|week| {
+            let event_number = (week % 12).to_string();
+            result(Series::FiveK, &event_number, saturday(week), "00:25:00")
+        })
+        .chain((0..10).map(|week| result(Series::Junior, "99", saturday(week), "00:10:00")))
+        .collect::<Vec<_>>();
+
+    let reached = milestones(&results)
+        .into_iter()
+        .map(|reached| reached.milestone)
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        reached,
+        [
+            Milestone::FirstRun,
+            Milestone::Events(10),
+            Milestone::JuniorRuns(10),
+            Milestone::Runs(25),
+            Milestone::Runs(50),
+        ]
+    );
+    assert_eq!(Milestone::Runs(50).to_string(), "50 club");
+}
@@ -0,0 +1,160 @@
+use chrono::Duration;
+use parkrust::client::requests::{
+    average_field_percentile, average_speed, duration_formatter, median_finishing_position,
+    roles_by_count, total_distance_km, Stats, VolunteerStats,
+};
+use parkrust::models::parkrun::{Role, RunResult, Series, VolunteerRole};
+use parkrust::testing::RunResultBuilder;
+use serde_json::json;
+
+fn result(series: Series, run_time: &str) -> RunResult {
+    RunResultBuilder::new()
+        .series(series)
+        .run_time(run_time)
+        .build()
+}
+
+#[test]
+fn speed_uses_course_distance() {
+    let five_k = result(Series::FiveK, "00:25:00");
+    let junior = result(Series::Junior, "00:10:00");
+
+    assert_eq!(five_k.series, Series::FiveK);
+    assert_eq!(junior.series, Series::Junior);
+    assert_eq!(five_k.speed(), Duration::minutes(5));
+    assert_eq!(junior.speed(), Duration::minutes(5));
+}
+
+#[test]
+fn average_speed_over_mixed_distances() {
+    let results = [
+        result(Series::FiveK, "00:25:00"),
+        result(Series::Junior, "00:14:00"),
+    ];
+
+    // 39 minutes over 7 km
+    assert_eq!(average_speed(&results), Some(Duration::seconds(334)));
+    assert_eq!(total_distance_km(&results), 7.0);
+    assert_eq!(Stats::from_results(&results).total_distance_km, 7.0);
+    assert_eq!(average_speed(&[]), None);
+}
+
+#[test]
+fn unknown_series_is_assumed_to_be_5k() {
+    let result = result(Series::Other(9), "00:25:00");
+    assert_eq!(result.series, Series::Other(9));
+    assert_eq!(result.distance_metres(), 5000);
+}
+
+fn placed(finish_position: u32, gender_position: u32) -> RunResult {
+    RunResultBuilder::new()
+        .finish_position(finish_position)
+        .gender_position(gender_position)
+        .build()
+}
+
+#[test]
+fn stats_without_results_are_none() {
+    let stats = Stats::from_results(&[]);
+    assert_eq!(stats.total_runs, 0);
+    assert_eq!(stats.total_time.num_seconds(), 0);
+    assert_eq!(stats.total_distance_km, 0.0);
+    assert_eq!(stats.average_time, None);
+    assert_eq!(stats.fastest_time, None);
+    assert_eq!(stats.average_speed, None);
+    assert_eq!(stats.best_finishing_position, None);
+    assert_eq!(stats.worst_finishing_position, None);
+    assert_eq!(stats.average_finishing_position, None);
+    assert_eq!(stats.median_finishing_position, None);
+    assert_eq!(stats.best_gender_position, None);
+    assert_eq!(stats.event_count(), 0);
+}
+
+#[test]
+fn finishing_positions() {
+    let results = [placed(40, 12), placed(10, 7), placed(25, 3)];
+    let stats = Stats::from_results(&results);
+
+    assert_eq!(stats.best_finishing_position, Some(10));
+    assert_eq!(stats.worst_finishing_position, Some(40));
+    assert_eq!(stats.average_finishing_position, Some(25.0));
+    assert_eq!(stats.median_finishing_position, Some(25.0));
+    // The best gender position needn't be from the best overall finish
+    assert_eq!(stats.best_gender_position, Some(3));
+}
+
+#[test]
+fn median_of_odd_and_even_counts() {
+    assert_eq!(median_finishing_position(&[placed(7, 1)]), Some(7.0));
+    assert_eq!(
+        median_finishing_position(&[placed(30, 1), placed(5, 1), placed(12, 1)]),
+        Some(12.0)
+    );
+    assert_eq!(
+        median_finishing_position(&[placed(30, 1), placed(5, 1), placed(12, 1), placed(9, 1)]),
+        Some(10.5)
+    );
+}
+
+#[test]
+fn average_field_percentile_skips_unknown_fields() {
+    let results = [placed(1, 1), placed(50, 20), placed(150, 60)];
+    let field_size = |result: &RunResult| match result.finish_position {
+        1 | 50 => Some(100),
+        _ => None,
+    };
+
+    // 99th and 50th percentiles, the third result's field size is unknown
+    assert_eq!(average_field_percentile(&results, field_size), Some(74.5));
+    assert_eq!(average_field_percentile(&results, |_| None), None);
+    assert_eq!(average_field_percentile(&[], |_| Some(100)), None);
+    // Positions outside the field are ignored rather than giving a negative percentile
+    assert_eq!(
+        average_field_percentile(&[placed(150, 60)], |_| Some(100)),
+        None
+    );
+}
+
+#[test]
+fn durations_are_formatted_as_run_times() {
+    assert_eq!(duration_formatter(Duration::seconds(0)), "00:00");
+    assert_eq!(duration_formatter(Duration::seconds(334)), "05:34");
+    assert_eq!(duration_formatter(Duration::seconds(3723)), "01:02:03");
+    assert_eq!(duration_formatter(Duration::milliseconds(59_999)), "00:59");
+    assert_eq!(duration_formatter(Duration::seconds(-5)), "00:00");
+}
+
+fn volunteer(run_id: &str, role: &str) -> VolunteerRole {
+    serde_json::from_value(json!({
+        "SeriesID": "1",
+        "EventNumber": "1",
+        "RunId": run_id,
+        "EventDate": "2023-01-07",
+        "AthleteID": "123456",
+        "VolunteerRole": role,
+    }))
+    .unwrap()
+}
+
+#[test]
+fn tied_roles_are_ordered_alphabetically() {
+    let roles = [
+        volunteer("1", "Timekeeper"),
+        volunteer("2", "Marshal"),
+        volunteer("3", "Timekeeper"),
+        volunteer("4", "Marshal"),
+        volunteer("5", "Barcode Scanning"),
+    ];
+
+    let stats = VolunteerStats::from_roles(&roles);
+
+    assert_eq!(stats.most_common_role(), Some(&Role::Marshal));
+    assert_eq!(
+        roles_by_count(&roles),
+        [
+            (Role::Marshal, 2),
+            (Role::Timekeeper, 2),
+            (Role::BarcodeScanning, 1)
+        ]
+    );
+}
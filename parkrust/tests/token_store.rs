@@ -0,0 +1,108 @@
+use chrono::{TimeZone, Utc};
+use parkrust::client::{FileTokenStore, Token, TokenStore};
+use parkrust::Error;
+use std::fs;
+
+fn token() -> Token {
+    Token {
+        access_token: String::from("access"),
+        refresh_token: String::from("refresh"),
+        expires_at: Utc.with_ymd_and_hms(2024, 1, 6, 9, 0, 0).unwrap(),
+    }
+}
+
+#[test]
+fn load_without_a_saved_token() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = FileTokenStore::new(dir.path().join("token.json"));
+    assert!(store.load().unwrap().is_none());
+}
+
+#[test]
+fn save_load_and_clear() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = FileTokenStore::new(dir.path().join("parkrust").join("token.json"));
+
+    store.save(&token()).unwrap();
+    assert!(store.path().exists());
+    assert!(store.load().unwrap() == Some(token()));
+
+    let refreshed = Token {
+        access_token: String::from("refreshed"),
+        ..token()
+    };
+    store.save(&refreshed).unwrap();
+    assert!(store.load().unwrap() == Some(refreshed));
+
+    store.clear().unwrap();
+    assert!(!store.path().exists());
+    assert!(store.load().unwrap().is_none());
+    // Clearing again is fine
+    store.clear().unwrap();
+}
+
+#[test]
+fn invalid_token_files_do_not_leak_their_contents() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = FileTokenStore::new(dir.path().join("token.json"));
+    fs::write(store.path(), r#"{"access_token": "secret""#).unwrap();
+
+    match store.load() {
+        Err(Error::TokenStore(message)) => assert!(!message.contains("secret"), "{message}"),
+        _ => panic!("expected a token store error"),
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn token_files_are_only_readable_by_the_user() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let store = FileTokenStore::new(dir.path().join("token.json"));
+    let mode = || fs::metadata(store.path()).unwrap().permissions().mode() & 0o777;
+
+    store.save(&token()).unwrap();
+    assert_eq!(mode(), 0o600);
+
+    // Existing files are tightened when the token is next saved
+    fs::set_permissions(store.path(), fs::Permissions::from_mode(0o644)).unwrap();
+    store.save(&token()).unwrap();
+    assert_eq!(mode(), 0o600);
+}
+
+// The environment is shared by the whole test binary, so every lookup is checked in one test
+#[test]
+fn config_dir_lookup() {
+    let dir = tempfile::tempdir().unwrap();
+
+    std::env::set_var("XDG_CONFIG_HOME", dir.path().join("config"));
+    std::env::set_var("HOME", dir.path().join("home"));
+    let store = FileTokenStore::in_config_dir().unwrap();
+    assert_eq!(
+        store.path(),
+        dir.path()
+            .join("config")
+            .join("parkrust")
+            .join("token.json")
+    );
+
+    // An empty XDG_CONFIG_HOME is ignored
+    std::env::set_var("XDG_CONFIG_HOME", "");
+    let store = FileTokenStore::in_config_dir().unwrap();
+    assert_eq!(
+        store.path(),
+        dir.path()
+            .join("home")
+            .join(".config")
+            .join("parkrust")
+            .join("token.json")
+    );
+
+    std::env::remove_var("XDG_CONFIG_HOME");
+    std::env::remove_var("HOME");
+    assert!(matches!(
+        FileTokenStore::in_config_dir(),
+        Err(Error::TokenStore(_))
+    ));
+}
@@ -19,8 +19,63 @@ pub enum Command {
         #[arg(short, long)]
         id: String,
 
-        /// Parkrun password
+        /// Parkrun password, only needed when there is no saved login
         #[arg(short, long)]
-        password: String,
+        password: Option<String>,
+    },
+    #[clap(arg_required_else_help = true)]
+    /// Print the athlete's profile and milestone progress
+    Me {
+        /// Parkrun athete ID e.g. A123456
+        #[arg(short, long)]
+        id: String,
+
+        /// Parkrun password, only needed when there is no saved login
+        #[arg(short, long)]
+        password: Option<String>,
+    },
+    #[clap(arg_required_else_help = true)]
+    /// Print stats for all of an athlete's results
+    Stats {
+        /// Parkrun athete ID e.g. A123456
+        #[arg(short, long)]
+        id: String,
+
+        /// Parkrun password, only needed when there is no saved login
+        #[arg(short, long)]
+        password: Option<String>,
+    },
+    #[clap(arg_required_else_help = true)]
+    /// Print stats for all of an athlete's volunteering
+    Volunteering {
+        /// Parkrun athete ID e.g. A123456
+        #[arg(short, long)]
+        id: String,
+
+        /// Parkrun password, only needed when there is no saved login
+        #[arg(short, long)]
+        password: Option<String>,
+    },
+    #[clap(arg_required_else_help = true)]
+    /// Print the athlete's PB progression and milestones
+    Progress {
+        /// Parkrun athete ID e.g. A123456
+        #[arg(short, long)]
+        id: String,
+
+        /// Parkrun password, only needed when there is no saved login
+        #[arg(short, long)]
+        password: Option<String>,
+    },
+    #[clap(arg_required_else_help = true)]
+    /// Print progress on parkrun tourism challenges
+    Challenges {
+        /// Parkrun athete ID e.g. A123456
+        #[arg(short, long)]
+        id: String,
+
+        /// Parkrun password, only needed when there is no saved login
+        #[arg(short, long)]
+        password: Option<String>,
     },
 }
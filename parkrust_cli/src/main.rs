@@ -1,7 +1,14 @@
 mod cli;
 
-use parkrust::client::ParkrunClient;
-use parkrust::models::parkrun::{Event, EventsQuery, Listable};
+use parkrust::challenges::Challenges;
+use parkrust::client::requests::{duration_formatter, Stats, VolunteerStats};
+use parkrust::client::{AuthenticatedParkrunClient, FileTokenStore, ParkrunClient};
+use parkrust::models::parkrun::{
+    Athlete, Event, EventsQuery, Listable, ResultsQuery, RunResult, Series, VolunteerRole,
+    VolunteersQuery,
+};
+use parkrust::progression::{milestones, pb_ladder, time_since_last_pb};
+use std::fmt::Display;
 
 use clap::Parser;
 use cli::{Cli, Command};
@@ -12,7 +19,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     match &args.command {
         Command::Run { id, password } => {
-            let mut client = ParkrunClient::new().authenticate(id, password).await?;
+            let mut client = client(id, password).await?;
 
             // println!("{:?}", client.get_me().await?);
 
@@ -30,8 +37,172 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .unwrap()
             );
         }
+        Command::Me { id, password } => {
+            let mut client = client(id, password).await?;
+            print_athlete(&client.get_me().await?);
+        }
+        Command::Stats { id, password } => {
+            let mut client = client(id, password).await?;
+            let results = RunResult::list(
+                ResultsQuery {
+                    athlete_id: id.clone(),
+                },
+                &mut client,
+            )
+            .await?;
+            print_stats(&Stats::from_results(&results));
+        }
+        Command::Volunteering { id, password } => {
+            let mut client = client(id, password).await?;
+            let roles = VolunteerRole::list(
+                VolunteersQuery {
+                    athlete_id: id.clone(),
+                },
+                &mut client,
+            )
+            .await?;
+            print_volunteer_stats(&VolunteerStats::from_roles(&roles));
+        }
+        Command::Progress { id, password } => {
+            let mut client = client(id, password).await?;
+            let results = RunResult::list(
+                ResultsQuery {
+                    athlete_id: id.clone(),
+                },
+                &mut client,
+            )
+            .await?;
+            print_progress(&results);
+        }
+        Command::Challenges { id, password } => {
+            let mut client = client(id, password).await?;
+            let results = RunResult::list(
+                ResultsQuery {
+                    athlete_id: id.clone(),
+                },
+                &mut client,
+            )
+            .await?;
+            let events = Event::list(
+                EventsQuery {
+                    athlete_id: id.clone(),
+                },
+                &mut client,
+            )
+            .await?;
+            print_challenges(&Challenges::from_results(&results, &events));
+        }
     }
 
     // println!("{:?}", client.get_events("718005").await?);
     Ok(())
 }
+
+/// Log in with the password if given, otherwise use the saved login. New logins are saved for
+/// later commands
+async fn client(
+    id: &str,
+    password: &Option<String>,
+) -> Result<AuthenticatedParkrunClient, Box<dyn std::error::Error>> {
+    let token_store = FileTokenStore::in_config_dir()?;
+    match password {
+        Some(password) => Ok(ParkrunClient::new()
+            .authenticate(id, password)
+            .await?
+            .with_token_store(token_store)?),
+        None => AuthenticatedParkrunClient::from_token_store(token_store)?
+            .ok_or_else(|| "No saved login, pass --password to log in".into()),
+    }
+}
+
+fn format_stat<T: Display>(value: Option<T>) -> String {
+    value.map_or_else(|| String::from("-"), |value| value.to_string())
+}
+
+fn print_athlete(athlete: &Athlete) {
+    println!("Hi {}!", athlete.first_name);
+    if let Some(home_run_name) = &athlete.home_run_name {
+        println!("Home run:         {home_run_name}");
+    }
+    if let Some(club_name) = &athlete.club_name {
+        println!("Club:             {club_name}");
+    }
+    if let Some(age_category) = athlete.age_category {
+        println!("Age category:     {age_category}");
+    }
+    println!("Runs:             {}", format_stat(athlete.run_count));
+    println!("Volunteers:       {}", format_stat(athlete.volunteer_count));
+    if let Some(progress) = athlete.next_run_milestone() {
+        println!(
+            "{} more runs until the {} club",
+            progress.remaining, progress.milestone
+        );
+    }
+    if let Some(progress) = athlete.next_volunteer_milestone() {
+        println!(
+            "{} more volunteer credits until the volunteer {} club",
+            progress.remaining, progress.milestone
+        );
+    }
+}
+
+fn print_stats(stats: &Stats) {
+    println!("Total runs:       {}", stats.total_runs);
+    println!("Locations:        {}", stats.event_count());
+    println!("Total time:       {}", stats.total_time);
+    println!("Total distance:   {:.1} km", stats.total_distance_km);
+    println!("Average time:     {}", format_stat(stats.average_time));
+    println!("Fastest time:     {}", format_stat(stats.fastest_time));
+    println!(
+        "Average min/km:   {}",
+        format_stat(stats.average_speed.map(duration_formatter))
+    );
+    println!(
+        "Best position:    {}",
+        format_stat(stats.best_finishing_position)
+    );
+    println!(
+        "Median position:  {}",
+        format_stat(stats.median_finishing_position)
+    );
+    println!(
+        "Best gender pos:  {}",
+        format_stat(stats.best_gender_position)
+    );
+}
+
+fn print_volunteer_stats(stats: &VolunteerStats) {
+    println!("Volunteer credits: {}", stats.total_credits);
+    println!("Roles:             {}", stats.total_roles);
+    println!("Locations:         {}", stats.event_count());
+    for (role, count) in &stats.roles_by_count {
+        println!("  {count:>4}  {role}");
+    }
+}
+
+fn print_progress(results: &[RunResult]) {
+    println!("PBs:");
+    for pb in pb_ladder(results, Series::FiveK) {
+        let improvement = pb
+            .improvement
+            .map(|improvement| format!(" (-{improvement})"))
+            .unwrap_or_default();
+        println!("  {}  {}{improvement}", pb.date, pb.run_time);
+    }
+    let today = chrono::Local::now().date_naive();
+    if let Some(since) = time_since_last_pb(results, Series::FiveK, today) {
+        println!("Days since PB:    {}", since.num_days());
+    }
+    println!("Milestones:");
+    for reached in milestones(results) {
+        println!("  {}  {}", reached.date, reached.milestone);
+    }
+}
+
+fn print_challenges(challenges: &Challenges) {
+    println!("Alphabet:         {}/26", challenges.alphabet.count());
+    println!("Wilson index:     {}", challenges.wilson_index);
+    println!("Compass:          {}/4", challenges.compass.points.len());
+    println!("Bingo:            {}/60", challenges.bingo.seconds.len());
+    println!("Stopwatch gaps:   {}", challenges.stopwatch.missing().len());
+}
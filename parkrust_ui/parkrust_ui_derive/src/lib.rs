@@ -1,13 +1,30 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{self, parse_macro_input, Fields, FieldsNamed, Ident, ItemStruct};
+use syn::{self, parse_macro_input, AttributeArgs, Fields, FieldsNamed, Ident, ItemStruct};
 
 #[proc_macro_attribute]
-pub fn table_data_type(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn table_data_type(attr: TokenStream, item: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(item as ItemStruct);
+    let args = parse_macro_input!(attr as AttributeArgs);
+    if let Some(arg) = args.first() {
+        return with_error(
+            &ast,
+            syn::Error::new_spanned(arg, "table_data_type does not take any arguments"),
+        );
+    }
     impl_table_data_type(ast)
 }
 
+/// Emit `error` alongside the unchanged item, so the only diagnostic is the macro's own
+fn with_error(ast: &ItemStruct, error: syn::Error) -> TokenStream {
+    let error = error.to_compile_error();
+    quote! {
+        #error
+        #ast
+    }
+    .into()
+}
+
 fn impl_table_data_type(struct_: ItemStruct) -> TokenStream {
     let name = &struct_.ident;
     let header_idents: Vec<Ident> = match &struct_.fields {
@@ -15,7 +32,21 @@ fn impl_table_data_type(struct_: ItemStruct) -> TokenStream {
             .iter()
             .map(|field| field.ident.clone().unwrap())
             .collect(),
-        _ => panic!("Only named structs are supported"),
+        Fields::Unnamed(fields) => {
+            let error = syn::Error::new_spanned(
+                fields,
+                "table_data_type only supports structs with named fields, the field names are \
+                 used as the table headers",
+            );
+            return with_error(&struct_, error);
+        }
+        Fields::Unit => {
+            let error = syn::Error::new_spanned(
+                &struct_.ident,
+                "table_data_type needs a struct with named fields to use as the table headers",
+            );
+            return with_error(&struct_, error);
+        }
     };
     let header_strings: Vec<String> = header_idents
         .iter()
@@ -0,0 +1,6 @@
+use parkrust_ui_derive::table_data_type;
+
+#[table_data_type]
+pub struct ResultRow;
+
+fn main() {}
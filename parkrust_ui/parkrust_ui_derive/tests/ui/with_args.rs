@@ -0,0 +1,8 @@
+use parkrust_ui_derive::table_data_type;
+
+#[table_data_type(headers = "Date")]
+pub struct ResultRow {
+    pub date: String,
+}
+
+fn main() {}
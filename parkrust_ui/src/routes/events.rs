@@ -1,4 +1,4 @@
-use parkrust::client::requests::{duration_formatter, fastest_time};
+use parkrust::client::requests::fastest_time;
 use parkrust::models::parkrun::{Event, RunResult};
 use parkrust_ui_derive::table_data_type;
 
@@ -35,7 +35,9 @@ impl EventTableData {
                 .filter(|result| &result.event_number == event_number)
                 .count()
                 .to_string(),
-            fastest_time: duration_formatter(fastest_time(&event_results)),
+            fastest_time: fastest_time(&event_results)
+                .map(|time| time.to_string())
+                .unwrap_or_default(),
         }
     }
 }
@@ -2,15 +2,14 @@ use crate::routes::events::Events;
 use crate::{
     components::{Card, LoadingSpinner},
     routes::results::Results,
-    services::parkrun::use_results,
+    services::parkrun::{use_athlete, use_results},
 };
 use chrono::prelude::*;
 use chrono::{Duration, Month, Utc};
 use num_traits::cast::FromPrimitive;
-use parkrust::client::requests::{
-    average_speed, average_time, duration_formatter, events, fastest_time, total_time,
-};
-use parkrust::models::parkrun::RunResult;
+use parkrust::client::requests::{duration_formatter, Stats};
+use parkrust::models::parkrun::{Athlete, RunResult, Series};
+use parkrust::progression::{current_pb, milestones, time_since_last_pb};
 use yew::prelude::*;
 
 #[function_component(Calendar)]
@@ -110,6 +109,50 @@ pub fn calendar() -> Html {
     }
 }
 
+#[function_component(Progress)]
+pub fn progress() -> Html {
+    let results_state = use_results();
+
+    let Some(results) = &*results_state else {
+        return html! {
+            <div> { "Loading..." } </div>
+        };
+    };
+
+    let today = Local::now().date_naive();
+    let last_pb = current_pb(results, Series::FiveK)
+        .zip(time_since_last_pb(results, Series::FiveK, today))
+        .map(|(pb, since)| {
+            html! {
+                <div class="mb-4 text-base text-gray-600 dark:text-white">
+                    { format!("PB of {} set {} days ago", pb.run_time, since.num_days()) }
+                </div>
+            }
+        });
+    let milestones = milestones(results)
+        .into_iter()
+        .rev()
+        .map(|reached| {
+            html! {
+                <tr>
+                    <td class="pr-4"> { reached.date.to_string() } </td>
+                    <td> { reached.milestone.to_string() } </td>
+                </tr>
+            }
+        })
+        .collect::<Html>();
+
+    html! {
+        <>
+            <div class="mt-3 text-3xl font-bold leading-8 mb-6"> { "Progress" } </div>
+            { for last_pb }
+            <table class="table-auto">
+                { milestones }
+            </table>
+        </>
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Properties)]
 pub struct StatCardProps {
     pub title: AttrValue,
@@ -140,6 +183,10 @@ pub fn stat_card(
     }
 }
 
+fn format_stat<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| String::from("-"), |value| value.to_string())
+}
+
 fn format_total_time(total_time: Duration) -> String {
     if total_time.num_minutes() < 100 {
         format!("{}m", total_time.num_minutes())
@@ -150,23 +197,51 @@ fn format_total_time(total_time: Duration) -> String {
     }
 }
 
+#[derive(Clone, PartialEq, Properties)]
+pub struct GreetingProps {
+    pub athlete: Athlete,
+}
+
+#[function_component(Greeting)]
+pub fn greeting(GreetingProps { athlete }: &GreetingProps) -> Html {
+    let milestone = athlete.next_run_milestone().map(|progress| {
+        html! {
+            <div class="mt-1 text-base text-gray-600 dark:text-white">
+                { format!("{} more runs until the {} club", progress.remaining, progress.milestone) }
+            </div>
+        }
+    });
+
+    html! {
+        <div class="col-span-12">
+            <div class="mt-3 text-3xl font-bold leading-8"> { format!("Hi {} 👋", athlete.first_name) } </div>
+            { for milestone }
+        </div>
+    }
+}
+
 #[function_component(Home)]
 pub fn home() -> Html {
     let results_state = use_results();
+    let athlete_state = use_athlete();
 
     match &*results_state {
         Some(results) => {
+            let stats = Stats::from_results(results);
             html! {
                 <div class="p-8">
                     <div class="grid grid-cols-12 gap-6">
-                        <StatCard emoji="🏃" title="Total runs" value={ results.len().to_string() } />
-                        <StatCard emoji="⏱" title="Avg time" value={ duration_formatter(average_time(results)) } />
-                        <StatCard emoji="⏳" title="Total time" value={ format_total_time(total_time(results)) } />
-                        <StatCard emoji="📍" title="Locations" value={ events(results).len().to_string() } />
-                        <StatCard emoji="🚀" title="Fastest time" value={ duration_formatter(fastest_time(results)) } />
+                        if let Some(athlete) = &*athlete_state {
+                            <Greeting athlete={athlete.clone()} />
+                        }
+                        <StatCard emoji="🏃" title="Total runs" value={ stats.total_runs.to_string() } />
+                        <StatCard emoji="⏱" title="Avg time" value={ format_stat(stats.average_time) } />
+                        <StatCard emoji="⏳" title="Total time" value={ format_total_time(stats.total_time.duration()) } />
+                        <StatCard emoji="📍" title="Locations" value={ stats.event_count().to_string() } />
+                        <StatCard emoji="🚀" title="Fastest time" value={ format_stat(stats.fastest_time) } />
                         <StatCard emoji="📅" title="This year" value={ results.iter().filter(|result| result.date().year() ==  Local::now().year()).count().to_string() } />
-                        <StatCard emoji="👪" title="Best position" value={ results.iter().map(|result| result.position()).min().unwrap().to_string() } />
-                        <StatCard emoji="⌚" title="Avg min/km" value={ duration_formatter(average_speed(results)) } />
+                        <StatCard emoji="👪" title="Best position" value={ format_stat(stats.best_finishing_position) } />
+                        <StatCard emoji="⌚" title="Avg min/km" value={ format_stat(stats.average_speed.map(duration_formatter)) } />
 
                         <div class="col-span-12 md:col-span-6">
                             <Card>
@@ -179,6 +254,11 @@ pub fn home() -> Html {
                                 <Results />
                             </Card>
                         </div>
+                        <div class="col-span-12 md:col-span-6">
+                            <Card>
+                                <Progress />
+                            </Card>
+                        </div>
                         <div class="transform col-span-12 md:col-span-6">
                             <Card>
                                 <div class="mt-3 text-3xl font-bold leading-8 mb-6"> { "Results" } </div>
@@ -1,3 +1,4 @@
+use parkrust::client::requests::duration_formatter;
 use parkrust::models::parkrun::{Event, RunResult};
 use parkrust_ui_derive::table_data_type;
 
@@ -10,21 +11,16 @@ use crate::services::parkrun::{use_events, use_results};
 pub struct ResultTableData {
     date: String,
     time: String,
+    pace: String,
     event_name: String,
 }
 
 impl ResultTableData {
-    pub fn from_parkrun_result(
-        RunResult {
-            event_date,
-            run_time,
-            ..
-        }: &RunResult,
-        run_event: &Event,
-    ) -> Self {
+    pub fn from_parkrun_result(result: &RunResult, run_event: &Event) -> Self {
         ResultTableData {
-            date: event_date.clone(),
-            time: run_time.clone(),
+            date: result.event_date.to_string(),
+            time: result.run_time.to_string(),
+            pace: format!("{}/km", duration_formatter(result.speed())),
             event_name: run_event.event_short_name.clone(),
         }
     }
@@ -1,11 +1,10 @@
-use chrono::prelude::*;
 use gloo::{
     console::log,
     storage::{errors::StorageError, LocalStorage, Storage},
 };
 use parkrust::{
-    client::{AuthenticatedParkrunClient, ParkrunClient, Token},
-    models::parkrun::{Event, EventsQuery, Listable, ResultsQuery, RunResult},
+    client::{AuthenticatedParkrunClient, ParkrunClient, Token, TokenStore},
+    models::parkrun::{Athlete, Event, EventsQuery, Listable, ResultsQuery, RunResult},
 };
 use std::rc::Rc;
 use yew::prelude::*;
@@ -23,6 +22,7 @@ pub struct AuthData {
 
 #[derive(Default, Clone, PartialEq)]
 pub struct Cache {
+    pub athlete_cache: Option<Athlete>,
     pub results_cache: Option<Vec<RunResult>>,
     pub events_cache: Option<Vec<Event>>,
 }
@@ -36,6 +36,7 @@ pub struct AuthState {
 pub enum AuthAction {
     Login(AuthData),
     Refresh(Token),
+    CacheAthlete(Athlete),
     CacheResults(Vec<RunResult>),
     CacheEvents(Vec<Event>),
 }
@@ -56,6 +57,27 @@ pub type AuthContext = UseReducerHandle<AuthState>;
 //     };
 // }
 
+#[hook]
+pub fn use_athlete() -> UseStateHandle<Option<Athlete>> {
+    let athlete = use_state(|| None);
+    let auth_ctx = use_context::<AuthContext>().unwrap();
+
+    {
+        let athlete = athlete.clone();
+        use_effect_with_deps(
+            move |_| {
+                wasm_bindgen_futures::spawn_local(async move {
+                    athlete.set(Some(get_user_athlete(&auth_ctx).await));
+                });
+                || ()
+            },
+            (),
+        );
+    }
+
+    athlete
+}
+
 #[hook]
 pub fn use_results() -> UseStateHandle<Option<Vec<RunResult>>> {
     let results = use_state(|| None);
@@ -101,17 +123,30 @@ pub fn use_events() -> UseStateHandle<Option<Vec<Event>>> {
 pub async fn get_client(
     auth_ctx: &UseReducerHandle<AuthState>,
 ) -> Option<AuthenticatedParkrunClient> {
-    let mut token = auth_ctx.data.clone()?.token;
-    if token.is_expired() {
-        log!("Token is expired");
-        log!("{:?}", token.expires_at.to_string());
-        token = refresh_token(&token.refresh_token).await;
-        log!("Got new token!", token.expires_at.to_string());
-        log!("{:?}", token.expires_at.to_string());
-        auth_ctx.dispatch(AuthAction::Refresh(token.clone()));
+    let token = auth_ctx.data.clone()?.token;
+    AuthenticatedParkrunClient::new(token)
+        .with_token_store(LocalStorageTokenStore)
+        .ok()
+}
+
+/// The client refreshes its token when needed, keep the auth state in sync with it
+fn sync_token(auth_ctx: &UseReducerHandle<AuthState>, client: &AuthenticatedParkrunClient) {
+    if auth_ctx.data.as_ref().map(|data| &data.token) != Some(&client.token) {
+        log!("Got new token!", client.token.expires_at.to_string());
+        auth_ctx.dispatch(AuthAction::Refresh(client.token.clone()));
+    }
+}
+
+pub async fn get_user_athlete(auth_ctx: &UseReducerHandle<AuthState>) -> Athlete {
+    if let Some(athlete) = &auth_ctx.cache.athlete_cache {
+        return athlete.clone();
     }
 
-    Some(AuthenticatedParkrunClient::new(token))
+    let mut client = get_client(auth_ctx).await.unwrap();
+    let athlete = client.get_me().await.unwrap();
+    sync_token(auth_ctx, &client);
+    auth_ctx.dispatch(AuthAction::CacheAthlete(athlete.clone()));
+    athlete
 }
 
 pub async fn get_user_results(auth_ctx: &UseReducerHandle<AuthState>) -> Vec<RunResult> {
@@ -124,6 +159,7 @@ pub async fn get_user_results(auth_ctx: &UseReducerHandle<AuthState>) -> Vec<Run
     let results = RunResult::list(ResultsQuery { athlete_id }, &mut client)
         .await
         .unwrap();
+    sync_token(auth_ctx, &client);
     auth_ctx.dispatch(AuthAction::CacheResults(results.clone()));
     results
 }
@@ -138,6 +174,7 @@ pub async fn get_user_events(auth_ctx: &UseReducerHandle<AuthState>) -> Vec<Even
     let events = Event::list(EventsQuery { athlete_id }, &mut client)
         .await
         .unwrap();
+    sync_token(auth_ctx, &client);
     auth_ctx.dispatch(AuthAction::CacheEvents(events.clone()));
     events
 }
@@ -150,7 +187,7 @@ impl Reducible for AuthState {
     fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
         match action {
             AuthAction::Login(auth_data) => {
-                store_token_data(&auth_data.token).unwrap();
+                LocalStorageTokenStore.save(&auth_data.token).unwrap();
                 store_althlete_id(&auth_data.athlete_id).unwrap();
                 Self {
                     data: Some(auth_data),
@@ -170,6 +207,18 @@ impl Reducible for AuthState {
                 }
                 .into()
             }
+            AuthAction::CacheAthlete(athlete) => {
+                let auth_data = self.data.clone();
+                let cache = self.cache.clone();
+                Self {
+                    data: auth_data,
+                    cache: Cache {
+                        athlete_cache: Some(athlete),
+                        ..cache
+                    },
+                }
+                .into()
+            }
             AuthAction::CacheResults(results) => {
                 let auth_data = self.data.clone();
                 let cache = self.cache.clone();
@@ -207,18 +256,37 @@ pub async fn login(id: &str, password: &str) -> Token {
     client.token
 }
 
-pub async fn refresh_token(refresh_token: &str) -> Token {
-    ParkrunClient::new()
-        .refresh_token(refresh_token)
-        .await
-        .unwrap()
-}
+/// Persists the token in the browser's local storage
+pub struct LocalStorageTokenStore;
 
-pub fn store_token_data(token: &Token) -> Result<(), StorageError> {
-    LocalStorage::set(ACCESS_TOKEN_KEY, token.access_token.clone())?;
-    LocalStorage::set(REFRESH_TOKEN_KEY, token.refresh_token.clone())?;
-    LocalStorage::set(TOKEN_EXPIRES_AT_KEY, token.expires_at)?;
-    Ok(())
+impl TokenStore for LocalStorageTokenStore {
+    fn load(&self) -> parkrust::Result<Option<Token>> {
+        let load_token = || -> Option<Token> {
+            Some(Token {
+                access_token: LocalStorage::get(ACCESS_TOKEN_KEY).ok()?,
+                refresh_token: LocalStorage::get(REFRESH_TOKEN_KEY).ok()?,
+                expires_at: LocalStorage::get(TOKEN_EXPIRES_AT_KEY).ok()?,
+            })
+        };
+        Ok(load_token())
+    }
+
+    fn save(&self, token: &Token) -> parkrust::Result<()> {
+        let store_token_data = || -> Result<(), StorageError> {
+            LocalStorage::set(ACCESS_TOKEN_KEY, token.access_token.clone())?;
+            LocalStorage::set(REFRESH_TOKEN_KEY, token.refresh_token.clone())?;
+            LocalStorage::set(TOKEN_EXPIRES_AT_KEY, token.expires_at)?;
+            Ok(())
+        };
+        store_token_data().map_err(|err| parkrust::Error::TokenStore(err.to_string()))
+    }
+
+    fn clear(&self) -> parkrust::Result<()> {
+        LocalStorage::delete(ACCESS_TOKEN_KEY);
+        LocalStorage::delete(REFRESH_TOKEN_KEY);
+        LocalStorage::delete(TOKEN_EXPIRES_AT_KEY);
+        Ok(())
+    }
 }
 
 pub fn store_althlete_id(athlete_id: &str) -> Result<(), StorageError> {
@@ -227,18 +295,9 @@ pub fn store_althlete_id(athlete_id: &str) -> Result<(), StorageError> {
 }
 
 pub fn get_auth_data_from_local_storage() -> Option<AuthData> {
-    let access_token: String = LocalStorage::get(ACCESS_TOKEN_KEY).ok()?;
     let athlete_id: String = LocalStorage::get(ALTHLETE_ID_KEY).ok()?;
-    let refresh_token: String = LocalStorage::get(REFRESH_TOKEN_KEY).ok()?;
-    let expires_at: DateTime<Utc> = LocalStorage::get(TOKEN_EXPIRES_AT_KEY).ok()?;
+    let token = LocalStorageTokenStore.load().ok()??;
 
     log!("Auth data found!");
-    Some(AuthData {
-        athlete_id,
-        token: Token {
-            access_token,
-            refresh_token,
-            expires_at,
-        },
-    })
+    Some(AuthData { athlete_id, token })
 }